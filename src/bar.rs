@@ -2,22 +2,36 @@ use crate::config::{BarConfig, BarPosition, MarginConfig, ModuleConfig};
 use crate::modules::{BarModuleFactory, ModuleInfo, ModuleLocation};
 use crate::popup::Popup;
 use crate::Ironbar;
-use color_eyre::Result;
+use color_eyre::{Report, Result};
 use glib::Propagation;
 use gtk::gdk::Monitor;
 use gtk::prelude::*;
 use gtk::{Application, ApplicationWindow, IconTheme, Orientation, Window, WindowType};
 use gtk_layer_shell::LayerShell;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 use std::time::Duration;
 use tracing::{debug, info};
 
 #[derive(Debug, Clone)]
 enum Inner {
-    New { config: Option<BarConfig> },
-    Loaded { popup: Rc<Popup> },
+    New {
+        config: Option<BarConfig>,
+    },
+    Loaded {
+        popup: Rc<Popup>,
+        config: BarConfig,
+        monitor: Monitor,
+        icon_theme: IconTheme,
+        /// The widget for each top-level module with a `name`.
+        module_widgets: Rc<RefCell<ModuleWidgets>>,
+    },
 }
 
+/// Widgets keyed by their module's configured name.
+type ModuleWidgets = HashMap<String, gtk::Widget>;
+
 #[derive(Debug, Clone)]
 pub struct Bar {
     name: String,
@@ -150,12 +164,16 @@ impl Bar {
             }
         }
 
-        let load_result = self.load_modules(config, monitor)?;
+        let load_result = self.load_modules(config.clone(), monitor)?;
 
         self.show(!start_hidden);
 
         self.inner = Inner::Loaded {
             popup: load_result.popup,
+            config,
+            monitor: monitor.clone(),
+            icon_theme: load_result.icon_theme,
+            module_widgets: Rc::new(RefCell::new(load_result.module_widgets)),
         };
         Ok(self)
     }
@@ -272,22 +290,31 @@ impl Bar {
         let popup = Popup::new(&info!(ModuleLocation::Left), config.popup_gap);
         let popup = Rc::new(popup);
 
+        let mut module_widgets = HashMap::new();
+
         if let Some(modules) = config.start {
             let info = info!(ModuleLocation::Left);
-            add_modules(&self.start, modules, &info, &self.ironbar, &popup)?;
+            let widgets = &mut module_widgets;
+            add_modules(&self.start, modules, &info, &self.ironbar, &popup, widgets)?;
         }
 
         if let Some(modules) = config.center {
             let info = info!(ModuleLocation::Center);
-            add_modules(&self.center, modules, &info, &self.ironbar, &popup)?;
+            let widgets = &mut module_widgets;
+            add_modules(&self.center, modules, &info, &self.ironbar, &popup, widgets)?;
         }
 
         if let Some(modules) = config.end {
             let info = info!(ModuleLocation::Right);
-            add_modules(&self.end, modules, &info, &self.ironbar, &popup)?;
+            let widgets = &mut module_widgets;
+            add_modules(&self.end, modules, &info, &self.ironbar, &popup, widgets)?;
         }
 
-        let result = BarLoadResult { popup };
+        let result = BarLoadResult {
+            popup,
+            icon_theme,
+            module_widgets,
+        };
 
         Ok(result)
     }
@@ -321,10 +348,89 @@ impl Bar {
             Inner::New { .. } => {
                 panic!("Attempted to get popup of uninitialized bar. This is a serious bug!")
             }
-            Inner::Loaded { popup } => popup.clone(),
+            Inner::Loaded { popup, .. } => popup.clone(),
         }
     }
 
+    /// Destroys the module with the provided name,
+    /// and creates it again in the same position from its existing config.
+    ///
+    /// Only top-level modules with a configured `name` can be reloaded.
+    pub fn reload_module(&self, name: &str) -> Result<()> {
+        let Inner::Loaded {
+            popup,
+            config,
+            monitor,
+            icon_theme,
+            module_widgets,
+        } = &self.inner
+        else {
+            return Err(Report::msg("Bar is not loaded"));
+        };
+
+        let locations = [
+            (&self.start, ModuleLocation::Left, &config.start),
+            (&self.center, ModuleLocation::Center, &config.center),
+            (&self.end, ModuleLocation::Right, &config.end),
+        ];
+
+        let Some((container, location, module)) =
+            locations
+                .into_iter()
+                .find_map(|(container, location, modules)| {
+                    modules
+                        .as_ref()
+                        .and_then(|modules| {
+                            modules.iter().find(|module| module.name() == Some(name))
+                        })
+                        .map(|module| (container, location, module))
+                })
+        else {
+            return Err(Report::msg(format!("No module named '{name}'")));
+        };
+
+        let Some(old_widget) = module_widgets.borrow().get(name).cloned() else {
+            return Err(Report::msg(format!("Widget for module '{name}' not found")));
+        };
+
+        let Some(position) = container
+            .children()
+            .iter()
+            .position(|child| child == &old_widget)
+        else {
+            return Err(Report::msg(format!("Widget for module '{name}' not found")));
+        };
+
+        debug!("Reloading module '{name}' on bar '{}'", self.name);
+
+        let app = self.window.application().expect("to exist");
+        let info = ModuleInfo {
+            app: &app,
+            location,
+            bar_position: config.position,
+            monitor,
+            output_name: &self.monitor_name,
+            icon_theme,
+        };
+
+        let module_factory = BarModuleFactory::new(self.ironbar.clone(), popup.clone()).into();
+        // the new instance is created before the old one is removed,
+        // so that the existing widget is kept if creation fails.
+        let new_widget = module.clone().create(&module_factory, container, &info)?;
+        container.reorder_child(&new_widget, position as i32);
+
+        container.remove(&old_widget);
+        module_widgets
+            .borrow_mut()
+            .insert(name.to_string(), new_widget.upcast());
+
+        // SAFETY: the widget has been removed from the bar and is not used again.
+        // Destroying it stops the old instance's tasks and removes its popup content.
+        unsafe { old_widget.destroy() };
+
+        Ok(())
+    }
+
     pub fn visible(&self) -> bool {
         self.window.is_visible()
     }
@@ -358,21 +464,35 @@ fn create_container(name: &str, orientation: Orientation) -> gtk::Box {
 #[derive(Debug)]
 struct BarLoadResult {
     popup: Rc<Popup>,
+    icon_theme: IconTheme,
+    module_widgets: ModuleWidgets,
 }
 
 /// Adds modules into a provided GTK box,
 /// which should be one of its left, center or right containers.
+///
+/// The widget for each named module is added to `module_widgets`.
 fn add_modules(
     content: &gtk::Box,
     modules: Vec<ModuleConfig>,
     info: &ModuleInfo,
     ironbar: &Rc<Ironbar>,
     popup: &Rc<Popup>,
+    module_widgets: &mut ModuleWidgets,
 ) -> Result<()> {
     let module_factory = BarModuleFactory::new(ironbar.clone(), popup.clone()).into();
 
     for config in modules {
-        config.create(&module_factory, content, info)?;
+        let name = config.name().map(str::to_string);
+        let widget = config.create(&module_factory, content, info)?;
+
+        // reloading uses the first module with a name,
+        // so later duplicates are not tracked
+        if let Some(name) = name {
+            module_widgets
+                .entry(name)
+                .or_insert_with(|| widget.upcast());
+        }
     }
 
     Ok(())
@@ -1,27 +1,49 @@
-use super::{Visibility, Workspace, WorkspaceClient, WorkspaceUpdate};
+use super::{
+    parse_workspace_num, FocusedWindow, SortKey, Visibility, WindowClient, Workspace,
+    WorkspaceClient, WorkspaceUpdate, COMMAND_SUCCESS,
+};
+use crate::clients::reconnect::ReconnectPolicy;
 use crate::{arc_mut, lock, send, spawn_blocking};
-use color_eyre::Result;
-use hyprland::data::{Workspace as HWorkspace, Workspaces};
-use hyprland::dispatch::{Dispatch, DispatchType, WorkspaceIdentifierWithSpecial};
+use color_eyre::{Report, Result};
+use hyprland::data::{Client as HClient, Monitors, Workspace as HWorkspace, Workspaces};
+use hyprland::dispatch::{
+    Dispatch, DispatchType, MonitorIdentifier, WorkspaceIdentifier, WorkspaceIdentifierWithSpecial,
+};
 use hyprland::event_listener::EventListener;
 use hyprland::prelude::*;
 use hyprland::shared::{HyprDataVec, WorkspaceType};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
 use tokio::sync::broadcast::{channel, Receiver, Sender};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 #[derive(Debug)]
 pub struct Client {
+    reconnect_policy: ReconnectPolicy,
     workspace_tx: Sender<WorkspaceUpdate>,
     _workspace_rx: Receiver<WorkspaceUpdate>,
+    monitor_tx: Sender<String>,
+    _monitor_rx: Receiver<String>,
+    window_tx: Sender<FocusedWindow>,
+    _window_rx: Receiver<FocusedWindow>,
 }
 
 impl Client {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(reconnect_policy: ReconnectPolicy) -> Self {
         let (workspace_tx, workspace_rx) = channel(16);
+        let (monitor_tx, monitor_rx) = channel(16);
+        let (window_tx, window_rx) = channel(16);
 
         let instance = Self {
+            reconnect_policy,
             workspace_tx,
             _workspace_rx: workspace_rx,
+            monitor_tx,
+            _monitor_rx: monitor_rx,
+            window_tx,
+            _window_rx: window_rx,
         };
 
         instance.listen_workspace_events();
@@ -32,10 +54,11 @@ impl Client {
         info!("Starting Hyprland event listener");
 
         let tx = self.workspace_tx.clone();
+        let monitor_tx = self.monitor_tx.clone();
+        let window_tx = self.window_tx.clone();
+        let reconnect_policy = self.reconnect_policy;
 
         spawn_blocking(move || {
-            let mut event_listener = EventListener::new();
-
             // we need a lock to ensure events don't run at the same time
             let lock = arc_mut!(());
 
@@ -43,139 +66,285 @@ impl Client {
             let active = Self::get_active_workspace().expect("Failed to get active workspace");
             let active = arc_mut!(Some(active));
 
-            {
-                let tx = tx.clone();
-                let lock = lock.clone();
-                let active = active.clone();
+            // cache the shown special workspaces, since hiding one does not say which
+            let special = arc_mut!(Self::get_special_workspaces());
 
-                event_listener.add_workspace_added_handler(move |workspace_type| {
-                    let _lock = lock!(lock);
-                    debug!("Added workspace: {workspace_type:?}");
+            let mut backoff = reconnect_policy.backoff();
 
-                    let workspace_name = get_workspace_name(workspace_type);
-                    let prev_workspace = lock!(active);
+            loop {
+                // a listener cannot be restarted once it has stopped,
+                // so each attempt needs a new one
+                let mut event_listener = EventListener::new();
+                Self::add_workspace_handlers(&mut event_listener, &tx, &lock, &active);
+                Self::add_special_handler(&mut event_listener, &tx, &lock, &special);
+                Self::add_monitor_handler(&mut event_listener, &monitor_tx);
+                Self::add_window_handler(&mut event_listener, &window_tx);
 
-                    let workspace = Self::get_workspace(&workspace_name, prev_workspace.as_ref());
+                let started = Instant::now();
 
-                    if let Some(workspace) = workspace {
-                        send!(tx, WorkspaceUpdate::Add(workspace));
-                    }
-                });
-            }
+                match event_listener.start_listener() {
+                    Ok(()) => warn!("Hyprland event listener closed"),
+                    Err(err) => error!("Hyprland event listener failed: {err:?}"),
+                }
 
-            {
-                let tx = tx.clone();
-                let lock = lock.clone();
-                let active = active.clone();
-
-                event_listener.add_workspace_change_handler(move |workspace_type| {
-                    let _lock = lock!(lock);
-
-                    let mut prev_workspace = lock!(active);
-
-                    debug!(
-                        "Received workspace change: {:?} -> {workspace_type:?}",
-                        prev_workspace.as_ref().map(|w| &w.id)
-                    );
-
-                    let workspace_name = get_workspace_name(workspace_type);
-                    let workspace = Self::get_workspace(&workspace_name, prev_workspace.as_ref());
-
-                    workspace.map_or_else(
-                        || {
-                            error!("Unable to locate workspace");
-                        },
-                        |workspace| {
-                            // there may be another type of update so dispatch that regardless of focus change
-                            if !workspace.visibility.is_focused() {
-                                Self::send_focus_change(&mut prev_workspace, workspace, &tx);
-                            }
-                        },
-                    );
-                });
-            }
+                // the listener was connected if it ran for a while,
+                // so start backing off from the beginning
+                if started.elapsed() > reconnect_policy.max_delay {
+                    backoff.reset();
+                }
 
-            {
-                let tx = tx.clone();
-                let lock = lock.clone();
-                let active = active.clone();
+                let Some(delay) = backoff.next_delay() else {
+                    error!("Giving up reconnecting to Hyprland");
+                    break;
+                };
 
-                event_listener.add_active_monitor_change_handler(move |event_data| {
-                    let _lock = lock!(lock);
-                    let workspace_type = event_data.workspace;
+                info!("Reconnecting to Hyprland in {}ms", delay.as_millis());
+                thread::sleep(delay);
+            }
+        });
+    }
 
-                    let mut prev_workspace = lock!(active);
+    /// Adds the handlers which forward workspace events to `tx`.
+    fn add_workspace_handlers(
+        event_listener: &mut EventListener,
+        tx: &Sender<WorkspaceUpdate>,
+        lock: &Arc<Mutex<()>>,
+        active: &Arc<Mutex<Option<Workspace>>>,
+    ) {
+        {
+            let tx = tx.clone();
+            let lock = lock.clone();
+            let active = active.clone();
 
-                    debug!(
-                        "Received active monitor change: {:?} -> {workspace_type:?}",
-                        prev_workspace.as_ref().map(|w| &w.name)
-                    );
+            event_listener.add_workspace_added_handler(move |workspace_type| {
+                let _lock = lock!(lock);
+                debug!("Added workspace: {workspace_type:?}");
 
-                    let workspace_name = get_workspace_name(workspace_type);
-                    let workspace = Self::get_workspace(&workspace_name, prev_workspace.as_ref());
+                let workspace_name = get_workspace_name(workspace_type);
+                let prev_workspace = lock!(active);
 
-                    if let Some((false, workspace)) =
-                        workspace.map(|w| (w.visibility.is_focused(), w))
-                    {
-                        Self::send_focus_change(&mut prev_workspace, workspace, &tx);
-                    } else {
-                        error!("Unable to locate workspace");
-                    }
-                });
-            }
+                let workspace = Self::get_workspace(&workspace_name, prev_workspace.as_ref());
 
-            {
-                let tx = tx.clone();
-                let lock = lock.clone();
+                if let Some(workspace) = workspace {
+                    send!(tx, WorkspaceUpdate::Add(workspace));
+                }
+            });
+        }
+
+        {
+            let tx = tx.clone();
+            let lock = lock.clone();
+            let active = active.clone();
 
-                event_listener.add_workspace_moved_handler(move |event_data| {
-                    let _lock = lock!(lock);
-                    let workspace_type = event_data.workspace;
-                    debug!("Received workspace move: {workspace_type:?}");
+            event_listener.add_workspace_change_handler(move |workspace_type| {
+                let _lock = lock!(lock);
 
-                    let mut prev_workspace = lock!(active);
+                let mut prev_workspace = lock!(active);
 
-                    let workspace_name = get_workspace_name(workspace_type);
-                    let workspace = Self::get_workspace(&workspace_name, prev_workspace.as_ref());
+                debug!(
+                    "Received workspace change: {:?} -> {workspace_type:?}",
+                    prev_workspace.as_ref().map(|w| &w.id)
+                );
 
-                    if let Some(workspace) = workspace {
-                        send!(tx, WorkspaceUpdate::Move(workspace.clone()));
+                let workspace_name = get_workspace_name(workspace_type);
+                let workspace = Self::get_workspace(&workspace_name, prev_workspace.as_ref());
 
+                workspace.map_or_else(
+                    || {
+                        error!("Unable to locate workspace");
+                    },
+                    |workspace| {
+                        // there may be another type of update so dispatch that regardless of focus change
                         if !workspace.visibility.is_focused() {
                             Self::send_focus_change(&mut prev_workspace, workspace, &tx);
                         }
+                    },
+                );
+            });
+        }
+
+        {
+            let tx = tx.clone();
+            let lock = lock.clone();
+            let active = active.clone();
+
+            event_listener.add_active_monitor_change_handler(move |event_data| {
+                let _lock = lock!(lock);
+                let workspace_type = event_data.workspace;
+
+                let mut prev_workspace = lock!(active);
+
+                debug!(
+                    "Received active monitor change: {:?} -> {workspace_type:?}",
+                    prev_workspace.as_ref().map(|w| &w.name)
+                );
+
+                let workspace_name = get_workspace_name(workspace_type);
+                let workspace = Self::get_workspace(&workspace_name, prev_workspace.as_ref());
+
+                if let Some((false, workspace)) = workspace.map(|w| (w.visibility.is_focused(), w))
+                {
+                    Self::send_focus_change(&mut prev_workspace, workspace, &tx);
+                } else {
+                    error!("Unable to locate workspace");
+                }
+            });
+        }
+
+        {
+            let tx = tx.clone();
+            let lock = lock.clone();
+            let active = active.clone();
+
+            event_listener.add_workspace_moved_handler(move |event_data| {
+                let _lock = lock!(lock);
+                let workspace_type = event_data.workspace;
+                debug!("Received workspace move: {workspace_type:?}");
+
+                let mut prev_workspace = lock!(active);
+
+                let workspace_name = get_workspace_name(workspace_type);
+                let workspace = Self::get_workspace(&workspace_name, prev_workspace.as_ref());
+
+                if let Some(workspace) = workspace {
+                    send!(tx, WorkspaceUpdate::Move(workspace.clone()));
+
+                    // keep the cached monitor in sync,
+                    // so the next focus event reports where the workspace is now
+                    if let Some(prev) = prev_workspace.as_mut() {
+                        if prev.id == workspace.id {
+                            prev.monitor.clone_from(&workspace.monitor);
+                        }
                     }
-                });
+
+                    if !workspace.visibility.is_focused() {
+                        Self::send_focus_change(&mut prev_workspace, workspace, &tx);
+                    }
+                }
+            });
+        }
+
+        {
+            let tx = tx.clone();
+            let lock = lock.clone();
+
+            event_listener.add_workspace_rename_handler(move |data| {
+                let _lock = lock!(lock);
+
+                send!(
+                    tx,
+                    WorkspaceUpdate::Rename {
+                        id: data.workspace_id as i64,
+                        name: data.workspace_name
+                    }
+                );
+            });
+        }
+
+        {
+            let tx = tx.clone();
+            let lock = lock.clone();
+
+            event_listener.add_workspace_destroy_handler(move |data| {
+                let _lock = lock!(lock);
+                debug!("Received workspace destroy: {data:?}");
+                send!(tx, WorkspaceUpdate::Remove(data.workspace_id as i64));
+            });
+        }
+    }
+
+    /// Adds the handler which sends visibility updates
+    /// when a special workspace is shown or hidden.
+    ///
+    /// `special` holds the special workspace shown on each monitor, keyed by monitor name.
+    fn add_special_handler(
+        event_listener: &mut EventListener,
+        tx: &Sender<WorkspaceUpdate>,
+        lock: &Arc<Mutex<()>>,
+        special: &Arc<Mutex<HashMap<String, Workspace>>>,
+    ) {
+        let tx = tx.clone();
+        let lock = lock.clone();
+        let special = special.clone();
+
+        event_listener.add_changed_special_handler(move |event_data| {
+            let _lock = lock!(lock);
+            debug!("Received special workspace change: {event_data:?}");
+
+            let mut special = lock!(special);
+            let mut updates = vec![];
+
+            if let Some(mut workspace) = special.remove(&event_data.monitor_name) {
+                workspace.visibility = Visibility::Hidden;
+                updates.push(workspace);
             }
 
-            {
-                let tx = tx.clone();
-                let lock = lock.clone();
+            // the name is empty when the special workspace is hidden
+            if !event_data.workspace_name.is_empty() {
+                if let Some(mut workspace) = Self::get_workspace(&event_data.workspace_name, None) {
+                    workspace.visibility = Visibility::visible();
 
-                event_listener.add_workspace_rename_handler(move |data| {
-                    let _lock = lock!(lock);
+                    special.insert(event_data.monitor_name.clone(), workspace.clone());
+                    updates.push(workspace);
+                }
+            }
 
-                    send!(
-                        tx,
-                        WorkspaceUpdate::Rename {
-                            id: data.workspace_id as i64,
-                            name: data.workspace_name
-                        }
-                    );
-                });
+            if !updates.is_empty() {
+                send!(tx, WorkspaceUpdate::Update(updates));
             }
+        });
+    }
 
-            {
-                event_listener.add_workspace_destroy_handler(move |data| {
-                    let _lock = lock!(lock);
-                    debug!("Received workspace destroy: {data:?}");
-                    send!(tx, WorkspaceUpdate::Remove(data.workspace_id as i64));
-                });
+    /// Adds the handler which forwards the focused monitor's name to `tx`.
+    fn add_monitor_handler(event_listener: &mut EventListener, tx: &Sender<String>) {
+        let tx = tx.clone();
+
+        event_listener.add_active_monitor_change_handler(move |event_data| {
+            let workspace_name = get_workspace_name(event_data.workspace);
+            let name = resolve_monitor_name(&event_data.monitor_name, &workspace_name);
+
+            debug!("Received monitor focus: {name}");
+
+            // there may be no receivers between subscriptions,
+            // which should not stop the listener
+            let _ = tx.send(name);
+        });
+    }
+
+    /// Creates a new receiver for the name of the focused monitor.
+    ///
+    /// The current monitor is sent on subscribing,
+    /// then again each time focus moves onto a different monitor.
+    /// All receivers share the client's event listener.
+    #[allow(dead_code)] // not yet used by any module
+    pub fn subscribe_monitor_focus(&self) -> Receiver<String> {
+        let rx = self.monitor_tx.subscribe();
+
+        match Monitors::get() {
+            Ok(monitors) => {
+                if let Some(monitor) = monitors.into_iter().find(|m| m.focused) {
+                    send!(self.monitor_tx, monitor.name);
+                }
             }
+            Err(err) => error!("Failed to get focused monitor: {err:?}"),
+        }
+
+        rx
+    }
 
-            event_listener
-                .start_listener()
-                .expect("Failed to start listener");
+    /// Adds the handler which forwards the active window to `tx`.
+    fn add_window_handler(event_listener: &mut EventListener, tx: &Sender<FocusedWindow>) {
+        let tx = tx.clone();
+
+        event_listener.add_active_window_change_handler(move |event_data| {
+            // hyprland sends `activewindow>>,` with empty fields
+            // when focus moves to an empty workspace
+            let window = event_data.map_or_else(FocusedWindow::default, |data| {
+                focused_window(data.class, data.title)
+            });
+
+            debug!("Received active window: {window:?}");
+            send!(tx, window);
         });
     }
 
@@ -215,6 +384,28 @@ impl Client {
             })
     }
 
+    /// Gets the special workspace shown on each monitor,
+    /// keyed by monitor name.
+    fn get_special_workspaces() -> HashMap<String, Workspace> {
+        let monitors = match Monitors::get() {
+            Ok(monitors) => monitors,
+            Err(err) => {
+                error!("Failed to get monitors: {err:?}");
+                return HashMap::new();
+            }
+        };
+
+        monitors
+            .into_iter()
+            // monitors without a special workspace shown report id 0
+            .filter(|monitor| monitor.special_workspace.id != 0)
+            .filter_map(|monitor| {
+                Self::get_workspace(&monitor.special_workspace.name, None)
+                    .map(|workspace| (monitor.name, workspace))
+            })
+            .collect()
+    }
+
     /// Gets the active workspace from the server.
     fn get_active_workspace() -> Result<Workspace> {
         let w = HWorkspace::get_active().map(|w| Workspace::from((Visibility::focused(), w)))?;
@@ -222,10 +413,46 @@ impl Client {
     }
 }
 
+impl WindowClient for Client {
+    fn subscribe_window_focus(&self) -> Receiver<FocusedWindow> {
+        let rx = self.window_tx.subscribe();
+
+        match HClient::get_active() {
+            Ok(window) => {
+                let window = window.map_or_else(FocusedWindow::default, |window| {
+                    focused_window(window.class, window.title)
+                });
+
+                send!(self.window_tx, window);
+            }
+            Err(err) => error!("Failed to get active window: {err:?}"),
+        }
+
+        rx
+    }
+}
+
 impl WorkspaceClient for Client {
-    fn focus(&self, id: String) -> Result<()> {
-        let identifier = id.parse::<i32>().map_or_else(
-            |_| WorkspaceIdentifierWithSpecial::Name(&id),
+    fn focus(&self, id: i64) -> Result<()> {
+        let workspace = Workspaces::get()?
+            .into_iter()
+            .find(|workspace| i64::from(workspace.id) == id)
+            .ok_or_else(|| Report::msg(format!("No workspace with id {id}")))?;
+
+        // special workspaces have negative ids,
+        // which the workspace dispatcher treats as a relative move
+        let dispatch = match workspace.name.strip_prefix("special:") {
+            Some(name) => DispatchType::ToggleSpecialWorkspace(Some(name.to_string())),
+            None => DispatchType::Workspace(WorkspaceIdentifierWithSpecial::Id(workspace.id)),
+        };
+
+        Dispatch::call(dispatch)?;
+        Ok(())
+    }
+
+    fn focus_by_name(&self, name: String) -> Result<()> {
+        let identifier = name.parse::<i32>().map_or_else(
+            |_| WorkspaceIdentifierWithSpecial::Name(&name),
             WorkspaceIdentifierWithSpecial::Id,
         );
 
@@ -233,6 +460,62 @@ impl WorkspaceClient for Client {
         Ok(())
     }
 
+    fn rename(&self, id: i64, new_name: String) -> Result<()> {
+        // special workspaces have negative ids, and their names are fixed
+        if id < 0 {
+            return Err(Report::msg(format!(
+                "Special workspace {id} cannot be renamed"
+            )));
+        }
+
+        let id = i32::try_from(id)?;
+
+        Dispatch::call(DispatchType::RenameWorkspace(id, Some(&new_name)))?;
+        Ok(())
+    }
+
+    fn move_workspace_to_output(&self, id: i64, output: String) -> Result<()> {
+        let monitors = Monitors::get()?.to_vec();
+        if !monitors.iter().any(|m| m.name == output) {
+            let names = monitors
+                .iter()
+                .map(|m| m.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            return Err(Report::msg(format!(
+                "Unknown output '{output}', expected one of: {names}"
+            )));
+        }
+
+        let id = i32::try_from(id)?;
+
+        Dispatch::call(DispatchType::MoveWorkspaceToMonitor(
+            WorkspaceIdentifier::Id(id),
+            MonitorIdentifier::Name(&output),
+        ))?;
+        Ok(())
+    }
+
+    fn run_command(&self, command: String) -> Result<Vec<String>> {
+        // match `hyprctl --batch` by allowing multiple `;` separated dispatches
+        let outcomes = command
+            .split(';')
+            .map(str::trim)
+            .filter(|command| !command.is_empty())
+            .map(|command| {
+                let (dispatcher, args) = command.split_once(' ').unwrap_or((command, ""));
+
+                match Dispatch::call(DispatchType::Custom(dispatcher, args.trim())) {
+                    Ok(()) => COMMAND_SUCCESS.to_string(),
+                    Err(err) => err.to_string(),
+                }
+            })
+            .collect();
+
+        Ok(outcomes)
+    }
+
     fn subscribe_workspace_change(&self) -> Receiver<WorkspaceUpdate> {
         let rx = self.workspace_tx.subscribe();
 
@@ -266,6 +549,41 @@ fn get_workspace_name(name: WorkspaceType) -> String {
     }
 }
 
+/// Creates the focused window from its class and title.
+/// The class is only set if it is not empty.
+fn focused_window(class: String, title: String) -> FocusedWindow {
+    FocusedWindow {
+        title,
+        app_id: Some(class).filter(|class| !class.is_empty()),
+    }
+}
+
+/// Gets the full monitor name from a `focusedmon` event.
+///
+/// Hyprland sends the monitor and workspace names separated by a comma,
+/// so the event may have been split in the wrong place
+/// if either name contains a comma.
+/// The original payload is matched against the known monitor names to recover it.
+fn resolve_monitor_name(monitor: &str, workspace: &str) -> String {
+    let payload = format!("{monitor},{workspace}");
+
+    Monitors::get()
+        .ok()
+        .and_then(|monitors| {
+            monitors
+                .into_iter()
+                .map(|m| m.name)
+                .filter(|name| {
+                    payload
+                        .strip_prefix(name.as_str())
+                        .is_some_and(|rest| rest.starts_with(','))
+                })
+                // prefer the longest match in case one name is a prefix of another
+                .max_by_key(String::len)
+        })
+        .unwrap_or_else(|| monitor.to_string())
+}
+
 /// Creates a function which determines if a workspace is visible.
 ///
 /// This function makes a Hyprland call that allocates so it should be cached when possible,
@@ -273,16 +591,35 @@ fn get_workspace_name(name: WorkspaceType) -> String {
 fn create_is_visible() -> impl Fn(&HWorkspace) -> bool {
     let monitors = hyprland::data::Monitors::get().map_or(Vec::new(), HyprDataVec::to_vec);
 
-    move |w| monitors.iter().any(|m| m.active_workspace.id == w.id)
+    // special workspaces are shown on top of the active workspace
+    move |w| {
+        monitors
+            .iter()
+            .any(|m| m.active_workspace.id == w.id || m.special_workspace.id == w.id)
+    }
 }
 
 impl From<(Visibility, HWorkspace)> for Workspace {
     fn from((visibility, workspace): (Visibility, HWorkspace)) -> Self {
+        let num = parse_workspace_num(&workspace.name);
+        // special workspaces always have negative ids
+        let is_special = workspace.id < 0;
+
+        let sort_key = if is_special {
+            SortKey::Special(workspace.name.clone())
+        } else {
+            SortKey::new(num, &workspace.name)
+        };
+
         Self {
             id: workspace.id as i64,
+            num,
             name: workspace.name,
             monitor: workspace.monitor,
             visibility,
+            urgent: false,
+            is_special,
+            sort_key,
         }
     }
 }
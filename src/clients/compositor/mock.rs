@@ -0,0 +1,287 @@
+use super::{parse_workspace_num, Visibility, Workspace, WorkspaceClient, WorkspaceUpdate};
+use crate::{arc_mut, lock, send, spawn};
+use color_eyre::{Report, Result};
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast::{channel, Receiver, Sender};
+use tokio::time::sleep;
+use tracing::{debug, info};
+
+/// Env var holding the path to the script to replay.
+pub const SCRIPT_ENV_VAR: &str = "IRONBAR_MOCK_COMPOSITOR";
+
+/// A scripted sequence of workspace updates,
+/// loaded from the JSON file at `IRONBAR_MOCK_COMPOSITOR`.
+#[derive(Debug, Deserialize)]
+struct Script {
+    /// The time to wait before each update, in milliseconds.
+    #[serde(default = "default_interval")]
+    interval: u64,
+    updates: Vec<WorkspaceUpdate>,
+}
+
+const fn default_interval() -> u64 {
+    1000
+}
+
+/// Fake compositor client,
+/// for exercising the workspaces module without a running compositor.
+///
+/// Updates are replayed from a script, or can be sent manually using [`Client::send`].
+/// Focus and other requests are accepted but have no effect.
+#[derive(Debug)]
+pub struct Client {
+    workspaces: Arc<Mutex<Vec<Workspace>>>,
+    workspace_tx: Sender<WorkspaceUpdate>,
+    _workspace_rx: Receiver<WorkspaceUpdate>,
+}
+
+impl Client {
+    /// Creates a new client,
+    /// replaying the script at the path set in `IRONBAR_MOCK_COMPOSITOR`.
+    pub(crate) fn new() -> Result<Self> {
+        let path = std::env::var(SCRIPT_ENV_VAR)
+            .map(PathBuf::from)
+            .map_err(|_| Report::msg(format!("{SCRIPT_ENV_VAR} is not set")))?;
+
+        let script = std::fs::read_to_string(&path)?;
+        let script: Script = serde_json::from_str(&script)?;
+
+        info!(
+            "Replaying {} workspace updates from {}",
+            script.updates.len(),
+            path.display()
+        );
+
+        let client = Self::empty();
+        client.replay(script.updates, Duration::from_millis(script.interval));
+
+        Ok(client)
+    }
+
+    /// Creates a new client with no workspaces.
+    fn empty() -> Self {
+        let (workspace_tx, workspace_rx) = channel(16);
+
+        Self {
+            workspaces: arc_mut!(vec![]),
+            workspace_tx,
+            _workspace_rx: workspace_rx,
+        }
+    }
+
+    /// Sends an update to all subscribers,
+    /// as if it came from the compositor.
+    #[allow(dead_code)] // only used by tests
+    pub fn send(&self, update: WorkspaceUpdate) {
+        debug!("Sending update: {update:?}");
+
+        apply(&mut lock!(self.workspaces), update.clone());
+
+        // there may not be any subscribers yet,
+        // in which case the update is still reflected in the next `Init`
+        let _ = self.workspace_tx.send(update);
+    }
+
+    /// Sends each update in turn,
+    /// waiting for `interval` before each.
+    fn replay(&self, updates: Vec<WorkspaceUpdate>, interval: Duration) {
+        let workspaces = self.workspaces.clone();
+        let tx = self.workspace_tx.clone();
+
+        spawn(async move {
+            for update in updates {
+                sleep(interval).await;
+
+                debug!("Replaying update: {update:?}");
+                apply(&mut lock!(workspaces), update.clone());
+                let _ = tx.send(update);
+            }
+        });
+    }
+}
+
+impl WorkspaceClient for Client {
+    fn focus(&self, id: i64) -> Result<()> {
+        debug!("Ignoring focus request for workspace {id}");
+        Ok(())
+    }
+
+    fn focus_by_name(&self, name: String) -> Result<()> {
+        debug!("Ignoring focus request for workspace '{name}'");
+        Ok(())
+    }
+
+    fn rename(&self, id: i64, new_name: String) -> Result<()> {
+        debug!("Ignoring rename request for workspace {id} to '{new_name}'");
+        Ok(())
+    }
+
+    fn move_workspace_to_output(&self, id: i64, output: String) -> Result<()> {
+        debug!("Ignoring move request for workspace {id} to '{output}'");
+        Ok(())
+    }
+
+    fn subscribe_workspace_change(&self) -> Receiver<WorkspaceUpdate> {
+        let rx = self.workspace_tx.subscribe();
+
+        let workspaces = lock!(self.workspaces).clone();
+        send!(self.workspace_tx, WorkspaceUpdate::Init(workspaces));
+
+        rx
+    }
+}
+
+/// Applies an update to the client's list of workspaces,
+/// following the same rules consumers are expected to,
+/// so that new subscribers are initialised with the current state.
+fn apply(workspaces: &mut Vec<Workspace>, update: WorkspaceUpdate) {
+    fn upsert(workspaces: &mut Vec<Workspace>, workspace: Workspace) {
+        match workspaces.iter_mut().find(|w| w.id == workspace.id) {
+            Some(existing) => *existing = workspace,
+            None => workspaces.push(workspace),
+        }
+    }
+
+    match update {
+        WorkspaceUpdate::Init(new) => *workspaces = new,
+        WorkspaceUpdate::Update(new) => {
+            for workspace in new {
+                upsert(workspaces, workspace);
+            }
+        }
+        WorkspaceUpdate::Add(workspace) | WorkspaceUpdate::Move(workspace) => {
+            upsert(workspaces, workspace);
+        }
+        WorkspaceUpdate::Remove(id) => workspaces.retain(|w| w.id != id),
+        WorkspaceUpdate::Focus { old, new } => {
+            if let Some(old) = old {
+                if let Some(workspace) = workspaces.iter_mut().find(|w| w.id == old.id) {
+                    workspace.visibility = if old.monitor == new.monitor {
+                        Visibility::Hidden
+                    } else {
+                        Visibility::visible()
+                    };
+                }
+            }
+
+            upsert(
+                workspaces,
+                Workspace {
+                    visibility: Visibility::focused(),
+                    ..new
+                },
+            );
+        }
+        WorkspaceUpdate::Rename { id, name } => {
+            if let Some(workspace) = workspaces.iter_mut().find(|w| w.id == id) {
+                workspace.num = parse_workspace_num(&name);
+                workspace.sort_key = workspace.sort_key.renamed(workspace.num, &name);
+                workspace.name = name;
+            }
+        }
+        WorkspaceUpdate::Urgent { id, urgent } => {
+            if let Some(workspace) = workspaces.iter_mut().find(|w| w.id == id) {
+                workspace.urgent = urgent;
+            }
+        }
+        WorkspaceUpdate::Unknown => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clients::compositor::SortKey;
+
+    fn workspace(id: i64) -> Workspace {
+        Workspace {
+            id,
+            name: id.to_string(),
+            num: Some(id as i32),
+            monitor: String::from("DP-1"),
+            visibility: Visibility::Hidden,
+            urgent: false,
+            is_special: false,
+            sort_key: SortKey::Number(id),
+        }
+    }
+
+    #[test]
+    fn test_subscribe_includes_sent_updates() {
+        let client = Client::empty();
+        client.send(WorkspaceUpdate::Add(workspace(1)));
+
+        let mut rx = client.subscribe_workspace_change();
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(WorkspaceUpdate::Init(workspaces)) if workspaces.len() == 1
+        ));
+
+        client.send(WorkspaceUpdate::Remove(1));
+        assert!(matches!(rx.try_recv(), Ok(WorkspaceUpdate::Remove(1))));
+    }
+
+    #[test]
+    fn test_init_replaces_workspaces() {
+        let mut workspaces = vec![];
+
+        apply(&mut workspaces, WorkspaceUpdate::Init(vec![workspace(1)]));
+        apply(&mut workspaces, WorkspaceUpdate::Add(workspace(2)));
+        apply(&mut workspaces, WorkspaceUpdate::Init(vec![workspace(3)]));
+
+        assert_eq!(workspaces.iter().map(|w| w.id).collect::<Vec<_>>(), vec![3]);
+    }
+
+    #[test]
+    fn test_update_keeps_other_workspaces() {
+        let mut workspaces = vec![workspace(1), workspace(2)];
+
+        let renamed = Workspace {
+            name: String::from("two"),
+            ..workspace(2)
+        };
+
+        apply(
+            &mut workspaces,
+            WorkspaceUpdate::Update(vec![renamed, workspace(3)]),
+        );
+
+        assert_eq!(
+            workspaces
+                .iter()
+                .map(|w| (w.id, w.name.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(1, "1"), (2, "two"), (3, "3")]
+        );
+    }
+
+    #[test]
+    fn test_parse_script() {
+        let script: Script = serde_json::from_str(
+            r#"{
+                "updates": [
+                    {
+                        "Add": {
+                            "id": 1,
+                            "name": "1",
+                            "num": 1,
+                            "monitor": "DP-1",
+                            "visibility": "Hidden",
+                            "urgent": false,
+                            "is_special": false,
+                            "sort_key": { "Number": 1 }
+                        }
+                    },
+                    { "Urgent": { "id": 1, "urgent": true } }
+                ]
+            }"#,
+        )
+        .expect("to parse script");
+
+        assert_eq!(script.interval, 1000);
+        assert_eq!(script.updates.len(), 2);
+    }
+}
@@ -1,6 +1,10 @@
+use crate::clients::reconnect::ReconnectPolicies;
+use crate::image::ImageProvider;
 use crate::{await_sync, register_fallible_client};
 use cfg_if::cfg_if;
 use color_eyre::{Help, Report, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
 use std::fmt::{Debug, Display, Formatter};
 use std::sync::Arc;
 use tokio::sync::broadcast;
@@ -8,14 +12,30 @@ use tracing::debug;
 
 #[cfg(feature = "workspaces+hyprland")]
 pub mod hyprland;
+#[cfg(feature = "mock-compositor")]
+pub mod mock;
+#[cfg(feature = "workspaces+niri")]
+pub mod niri;
+#[cfg(feature = "workspaces+river")]
+pub mod river;
 #[cfg(feature = "workspaces+sway")]
 pub mod sway;
+#[cfg(feature = "workspaces+wayfire")]
+pub mod wayfire;
 
 pub enum Compositor {
     #[cfg(feature = "workspaces+sway")]
     Sway,
     #[cfg(feature = "workspaces+hyprland")]
     Hyprland,
+    #[cfg(feature = "workspaces+river")]
+    River,
+    #[cfg(feature = "workspaces+niri")]
+    Niri,
+    #[cfg(feature = "workspaces+wayfire")]
+    Wayfire,
+    #[cfg(feature = "mock-compositor")]
+    Mock,
     Unsupported,
 }
 
@@ -29,6 +49,14 @@ impl Display for Compositor {
                 Self::Sway => "Sway",
                 #[cfg(feature = "workspaces+hyprland")]
                 Self::Hyprland => "Hyprland",
+                #[cfg(feature = "workspaces+river")]
+                Self::River => "River",
+                #[cfg(feature = "workspaces+niri")]
+                Self::Niri => "Niri",
+                #[cfg(feature = "workspaces+wayfire")]
+                Self::Wayfire => "Wayfire",
+                #[cfg(feature = "mock-compositor")]
+                Self::Mock => "Mock",
                 Self::Unsupported => "Unsupported",
             }
         )
@@ -39,6 +67,11 @@ impl Compositor {
     /// Attempts to get the current compositor.
     /// This is done by checking system env vars.
     fn get_current() -> Self {
+        #[cfg(feature = "mock-compositor")]
+        if std::env::var(mock::SCRIPT_ENV_VAR).is_ok() {
+            return Self::Mock;
+        }
+
         if std::env::var("SWAYSOCK").is_ok() {
             cfg_if! {
                 if #[cfg(feature = "workspaces+sway")] { Self::Sway }
@@ -49,43 +82,222 @@ impl Compositor {
                 if #[cfg(feature = "workspaces+hyprland")] { Self::Hyprland }
                 else { tracing::error!("Not compiled with Hyprland support"); Self::Unsupported }
             }
+        } else if std::env::var("XDG_CURRENT_DESKTOP").is_ok_and(|desktop| desktop == "river") {
+            cfg_if! {
+                if #[cfg(feature = "workspaces+river")] { Self::River }
+                else { tracing::error!("Not compiled with River support"); Self::Unsupported }
+            }
+        } else if std::env::var("NIRI_SOCKET").is_ok() {
+            cfg_if! {
+                if #[cfg(feature = "workspaces+niri")] { Self::Niri }
+                else { tracing::error!("Not compiled with Niri support"); Self::Unsupported }
+            }
+        } else if std::env::var("WAYFIRE_SOCKET").is_ok() {
+            cfg_if! {
+                if #[cfg(feature = "workspaces+wayfire")] { Self::Wayfire }
+                else { tracing::error!("Not compiled with Wayfire support"); Self::Unsupported }
+            }
         } else {
             Self::Unsupported
         }
     }
 
-    /// Creates a new instance of
-    /// the workspace client for the current compositor.
-    pub fn create_workspace_client() -> Result<Arc<dyn WorkspaceClient + Send + Sync>> {
+    /// Creates new instances of
+    /// the clients for the current compositor.
+    ///
+    /// Where a compositor provides more than one client,
+    /// they share a single instance.
+    /// Each uses the reconnect policy for the compositor's name.
+    pub fn create_clients(reconnect_policies: &ReconnectPolicies) -> Result<CompositorClients> {
         let current = Self::get_current();
-        debug!("Getting workspace client for: {current}");
+        debug!("Getting compositor clients for: {current}");
+
+        #[allow(unused_variables)] // not all compositors reconnect
+        let reconnect_policy = reconnect_policies.get(&current.to_string());
         match current {
             #[cfg(feature = "workspaces+sway")]
-            Self::Sway => await_sync(async { sway::Client::new().await })
-                .map(|client| Arc::new(client) as Arc<dyn WorkspaceClient + Send + Sync>),
+            Self::Sway => await_sync(async { sway::Client::new(reconnect_policy).await })
+                .map(|client| CompositorClients::with_windows(Arc::new(client))),
             #[cfg(feature = "workspaces+hyprland")]
-            Self::Hyprland => Ok(Arc::new(hyprland::Client::new())),
-            Self::Unsupported => Err(Report::msg("Unsupported compositor")
-                .note("Currently workspaces are only supported by Sway and Hyprland")),
+            Self::Hyprland => Ok(CompositorClients::with_windows(Arc::new(
+                hyprland::Client::new(reconnect_policy),
+            ))),
+            #[cfg(feature = "workspaces+river")]
+            Self::River => river::Client::new()
+                .map(|client| CompositorClients::workspaces_only(Arc::new(client))),
+            #[cfg(feature = "workspaces+niri")]
+            Self::Niri => niri::Client::new(reconnect_policy)
+                .map(|client| CompositorClients::workspaces_only(Arc::new(client))),
+            #[cfg(feature = "workspaces+wayfire")]
+            Self::Wayfire => wayfire::Client::new(reconnect_policy)
+                .map(|client| CompositorClients::workspaces_only(Arc::new(client))),
+            #[cfg(feature = "mock-compositor")]
+            Self::Mock => mock::Client::new()
+                .map(|client| CompositorClients::workspaces_only(Arc::new(client))),
+            Self::Unsupported => Err(Report::new(UnsupportedCompositorError)
+                .note("Currently workspaces are only supported by Sway, Hyprland, River, Niri and Wayfire")),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+/// The clients provided by the current compositor.
+pub struct CompositorClients {
+    pub workspaces: Arc<dyn WorkspaceClient>,
+    /// This is `None` if the compositor does not support window focus.
+    #[cfg(any(feature = "workspaces+sway", feature = "workspaces+hyprland"))]
+    pub windows: Option<Arc<dyn WindowClient>>,
+}
+
+impl CompositorClients {
+    #[cfg(any(feature = "workspaces+sway", feature = "workspaces+hyprland"))]
+    fn with_windows<T: WorkspaceClient + WindowClient + 'static>(client: Arc<T>) -> Self {
+        Self {
+            workspaces: client.clone(),
+            windows: Some(client),
+        }
+    }
+
+    #[cfg(any(
+        feature = "workspaces+river",
+        feature = "workspaces+niri",
+        feature = "workspaces+wayfire",
+        feature = "mock-compositor"
+    ))]
+    fn workspaces_only<T: WorkspaceClient + 'static>(client: Arc<T>) -> Self {
+        Self {
+            workspaces: client,
+            #[cfg(any(feature = "workspaces+sway", feature = "workspaces+hyprland"))]
+            windows: None,
+        }
+    }
+}
+
+/// Error returned when creating a client under an unsupported compositor.
+///
+/// Modules can check for this using `Report::is`
+/// to show nothing rather than fail to load.
+#[derive(Debug, Clone, Copy)]
+pub struct UnsupportedCompositorError;
+
+impl Display for UnsupportedCompositorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unsupported compositor")
+    }
+}
+
+impl std::error::Error for UnsupportedCompositorError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "mock-compositor", derive(Deserialize))]
 pub struct Workspace {
     /// Unique identifier
     pub id: i64,
     /// Workspace friendly name
     pub name: String,
+    /// Workspace number, for workspaces whose name starts with one.
+    /// For example, `1: web` has the number `1`.
+    pub num: Option<i32>,
     /// Name of the monitor (output) the workspace is located on
     pub monitor: String,
     /// How visible the workspace is
     pub visibility: Visibility,
+    /// Whether the workspace contains a window requesting attention
+    pub urgent: bool,
+    /// Whether this is a special (scratchpad) workspace,
+    /// which is shown on top of the regular workspaces.
+    /// Currently only Hyprland has these.
+    pub is_special: bool,
+    /// Key used to order workspaces.
+    pub sort_key: SortKey,
+}
+
+/// Key for ordering workspaces,
+/// so that they are sorted the same way regardless of compositor.
+///
+/// Numbered workspaces come first, ordered by number,
+/// followed by named workspaces ordered by name,
+/// then special workspaces ordered by name.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "mock-compositor", derive(Deserialize))]
+pub enum SortKey {
+    Number(i64),
+    Name(String),
+    Special(String),
+}
+
+impl SortKey {
+    /// Gets the key for a regular workspace,
+    /// using its number if it has one.
+    pub fn new(num: Option<i32>, name: &str) -> Self {
+        num.map_or_else(
+            || Self::Name(name.to_string()),
+            |num| Self::Number(i64::from(num)),
+        )
+    }
+
+    /// Gets the key for this workspace after it has been renamed.
+    /// Special workspaces are kept after all others.
+    pub fn renamed(&self, num: Option<i32>, name: &str) -> Self {
+        match self {
+            Self::Special(_) => Self::Special(name.to_string()),
+            _ => Self::new(num, name),
+        }
+    }
+}
+
+/// Parses the number from the start of a workspace name,
+/// following sway's rules for numbered workspaces.
+pub fn parse_workspace_num(name: &str) -> Option<i32> {
+    let end = name
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(name.len());
+
+    name[..end].parse().ok()
+}
+
+/// Map of workspace names to the labels to show in their place.
+///
+/// Labels can be [images](images) or text.
+/// Prefix a label with `text:` to always show it as text.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct WorkspaceNameMap(HashMap<String, String>);
+
+/// The label to show for a workspace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkspaceLabel<'a> {
+    /// Image input, to be loaded using `ImageProvider`.
+    Image(&'a str),
+    Text(&'a str),
+}
+
+impl WorkspaceNameMap {
+    /// Gets the label for a workspace.
+    ///
+    /// Workspaces are looked up by name, then by number.
+    /// If neither is mapped, the name is used.
+    pub fn label<'a>(&'a self, name: &'a str, num: Option<i32>) -> WorkspaceLabel<'a> {
+        let label = self
+            .0
+            .get(name)
+            .or_else(|| num.and_then(|num| self.0.get(&num.to_string())))
+            .map_or(name, String::as_str);
+
+        if let Some(text) = label.strip_prefix("text:") {
+            WorkspaceLabel::Text(text)
+        } else if ImageProvider::is_definitely_image_input(label) {
+            WorkspaceLabel::Image(label)
+        } else {
+            WorkspaceLabel::Text(label)
+        }
+    }
 }
 
 /// Indicates workspace visibility. Visible workspaces have a boolean flag to indicate if they are also focused.
 /// Yes, this is the same signature as Option<bool>, but it's impl is a lot more suited for our case.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "mock-compositor", derive(Deserialize))]
 pub enum Visibility {
     Visible(bool),
     Hidden,
@@ -100,10 +312,14 @@ impl Visibility {
         Self::Visible(true)
     }
 
+    /// Whether the workspace is shown on its monitor.
+    /// This is also true for the focused workspace.
     pub fn is_visible(self) -> bool {
         matches!(self, Self::Visible(_))
     }
 
+    /// Whether the workspace is the single focused workspace,
+    /// across all monitors.
     pub fn is_focused(self) -> bool {
         if let Self::Visible(focused) = self {
             focused
@@ -111,13 +327,41 @@ impl Visibility {
             false
         }
     }
+
+    /// Whether the workspace is not shown on any monitor.
+    pub fn is_hidden(self) -> bool {
+        matches!(self, Self::Hidden)
+    }
+}
+
+/// A direction to move a workspace in, relative to its neighbours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveDirection {
+    Left,
+    Right,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "mock-compositor", derive(Deserialize))]
 pub enum WorkspaceUpdate {
-    /// Provides an initial list of workspaces.
-    /// This is re-sent to all subscribers when a new subscription is created.
+    /// Provides the full list of workspaces.
+    /// Consumers must discard any existing state and replace it with this list.
+    ///
+    /// This is re-sent to all subscribers when a new subscription is created,
+    /// and after reconnecting to the compositor,
+    /// as updates may have been missed while disconnected.
     Init(Vec<Workspace>),
+    /// Adds or refreshes each of these workspaces.
+    /// Workspaces not in the list are left untouched.
+    #[cfg_attr(
+        not(any(
+            feature = "workspaces+river",
+            feature = "workspaces+niri",
+            feature = "workspaces+wayfire"
+        )),
+        allow(dead_code)
+    )]
+    Update(Vec<Workspace>),
     Add(Workspace),
     Remove(i64),
     Move(Workspace),
@@ -132,6 +376,12 @@ pub enum WorkspaceUpdate {
         name: String,
     },
 
+    /// Declares the urgent state of a workspace changed.
+    Urgent {
+        id: i64,
+        urgent: bool,
+    },
+
     /// An update was triggered by the compositor but this was not mapped by Ironbar.
     ///
     /// This is purely used for ergonomics within the compositor clients
@@ -139,12 +389,437 @@ pub enum WorkspaceUpdate {
     Unknown,
 }
 
+/// Gets the workspaces located on the named output (monitor).
+///
+/// Workspaces without a monitor are not mapped to any output,
+/// so are never included.
+pub fn filter_by_output<'a>(workspaces: &'a [Workspace], output: &str) -> Vec<&'a Workspace> {
+    workspaces
+        .iter()
+        .filter(|w| !w.monitor.is_empty() && w.monitor == output)
+        .collect()
+}
+
+/// Compares two snapshots of the full workspace list,
+/// returning the updates which take consumers from `old` to `new`.
+///
+/// This is for compositors which report their full workspace state
+/// rather than individual changes.
+///
+/// Visibility changes not covered by the focus moving,
+/// such as the shown workspace changing on an unfocused output,
+/// are sent as a single `Update`.
+#[cfg(any(
+    feature = "workspaces+river",
+    feature = "workspaces+niri",
+    feature = "workspaces+wayfire"
+))]
+pub fn diff_workspaces(old: &[Workspace], new: &[Workspace]) -> Vec<WorkspaceUpdate> {
+    let mut updates = vec![];
+    let mut changed = vec![];
+
+    for workspace in old {
+        if !new.iter().any(|w| w.id == workspace.id) {
+            updates.push(WorkspaceUpdate::Remove(workspace.id));
+        }
+    }
+
+    for workspace in new {
+        match old.iter().find(|w| w.id == workspace.id) {
+            Some(prev) => {
+                if prev.name != workspace.name {
+                    updates.push(WorkspaceUpdate::Rename {
+                        id: workspace.id,
+                        name: workspace.name.clone(),
+                    });
+                }
+
+                if prev.monitor != workspace.monitor {
+                    updates.push(WorkspaceUpdate::Move(workspace.clone()));
+                } else if prev.visibility != workspace.visibility {
+                    changed.push(workspace.clone());
+                }
+            }
+            None => updates.push(WorkspaceUpdate::Add(workspace.clone())),
+        }
+    }
+
+    let focused = |workspaces: &[Workspace]| {
+        workspaces
+            .iter()
+            .find(|w| w.visibility.is_focused())
+            .cloned()
+    };
+
+    let old = focused(old);
+    let new = focused(new).filter(|new| old.as_ref().map(|w| w.id) != Some(new.id));
+
+    // the focus update already covers the workspaces it moves between
+    if let Some(new) = &new {
+        changed.retain(|w| w.id != new.id && old.as_ref().map(|old| old.id) != Some(w.id));
+    }
+
+    if !changed.is_empty() {
+        updates.push(WorkspaceUpdate::Update(changed));
+    }
+
+    if let Some(new) = new {
+        updates.push(WorkspaceUpdate::Focus { old, new });
+    }
+
+    updates
+}
+
+/// The result message for a successful command,
+/// as returned by [`WorkspaceClient::run_command`].
+#[cfg(any(feature = "workspaces+sway", feature = "workspaces+hyprland"))]
+pub const COMMAND_SUCCESS: &str = "success";
+
 pub trait WorkspaceClient: Debug + Send + Sync {
+    /// Requests the workspace with this id is focused.
+    fn focus(&self, id: i64) -> Result<()>;
+
     /// Requests the workspace with this name is focused.
-    fn focus(&self, name: String) -> Result<()>;
+    fn focus_by_name(&self, name: String) -> Result<()>;
+
+    /// Requests the workspace with this name is focused,
+    /// preferring the one on the named output (monitor).
+    ///
+    /// This is used for favourites which do not yet exist.
+    /// Compositors which only create workspaces on the focused output
+    /// should focus the output first.
+    fn focus_by_name_on_output(&self, name: String, _output: &str) -> Result<()> {
+        self.focus_by_name(name)
+    }
+
+    /// Requests the workspace with this id is renamed.
+    fn rename(&self, id: i64, new_name: String) -> Result<()>;
+
+    /// Requests the workspace with this id is moved onto the named output (monitor).
+    fn move_workspace_to_output(&self, id: i64, output: String) -> Result<()>;
+
+    /// Requests the workspace with this id swaps position
+    /// with its neighbour in the given direction.
+    fn move_workspace_position(&self, _id: i64, _direction: MoveDirection) -> Result<()> {
+        Err(Report::msg(
+            "Moving workspace position is not supported by this compositor",
+        ))
+    }
+
+    /// Runs a raw compositor command,
+    /// returning a result message for each command it contains.
+    /// Successful commands give `success`,
+    /// and failed commands give the compositor's error message.
+    ///
+    /// This is an escape hatch for actions without a dedicated method,
+    /// so the command syntax is specific to the compositor.
+    fn run_command(&self, _command: String) -> Result<Vec<String>> {
+        Err(Report::msg(
+            "Running commands is not supported by this compositor",
+        ))
+    }
+
+    /// Whether the client is currently connected to the compositor.
+    fn is_healthy(&self) -> bool {
+        true
+    }
 
     /// Creates a new to workspace event receiver.
     fn subscribe_workspace_change(&self) -> broadcast::Receiver<WorkspaceUpdate>;
 }
 
 register_fallible_client!(dyn WorkspaceClient, workspaces);
+
+/// The currently focused window.
+#[cfg(any(feature = "workspaces+sway", feature = "workspaces+hyprland"))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FocusedWindow {
+    /// Window title.
+    /// This is empty if nothing is focused,
+    /// or focus is on a container rather than a window.
+    pub title: String,
+    /// The app id, for Wayland windows,
+    /// or the window class, for XWayland windows.
+    /// On Hyprland, this is always the window class.
+    pub app_id: Option<String>,
+}
+
+#[cfg(any(feature = "workspaces+sway", feature = "workspaces+hyprland"))]
+pub trait WindowClient: Debug + Send + Sync {
+    /// Creates a new receiver for focused window changes.
+    ///
+    /// The current window is sent immediately.
+    /// An update is then sent whenever focus moves,
+    /// or the focused window's title changes.
+    fn subscribe_window_focus(&self) -> broadcast::Receiver<FocusedWindow>;
+}
+
+#[cfg(any(feature = "workspaces+sway", feature = "workspaces+hyprland"))]
+register_fallible_client!(dyn WindowClient, windows);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(id: i64, name: &str, monitor: &str, visibility: Visibility) -> Workspace {
+        Workspace {
+            id,
+            name: name.to_string(),
+            num: parse_workspace_num(name),
+            monitor: monitor.to_string(),
+            visibility,
+            urgent: false,
+            is_special: false,
+            sort_key: SortKey::new(parse_workspace_num(name), name),
+        }
+    }
+
+    #[test]
+    #[cfg(any(
+        feature = "workspaces+river",
+        feature = "workspaces+niri",
+        feature = "workspaces+wayfire"
+    ))]
+    fn test_diff_workspaces() {
+        let old = [
+            workspace(1, "1", "DP-1", Visibility::focused()),
+            workspace(2, "2", "DP-1", Visibility::Hidden),
+            workspace(3, "3", "DP-1", Visibility::Hidden),
+        ];
+
+        let new = [
+            workspace(1, "1", "DP-1", Visibility::visible()),
+            workspace(2, "two", "DP-1", Visibility::Hidden),
+            workspace(4, "4", "HDMI-A-1", Visibility::focused()),
+        ];
+
+        let updates = diff_workspaces(&old, &new);
+        assert_eq!(updates.len(), 4);
+
+        assert!(matches!(updates[0], WorkspaceUpdate::Remove(3)));
+        assert!(matches!(&updates[1], WorkspaceUpdate::Rename { id: 2, name } if name == "two"));
+        assert!(matches!(&updates[2], WorkspaceUpdate::Add(w) if w.id == 4));
+        assert!(matches!(
+            &updates[3],
+            WorkspaceUpdate::Focus { old: Some(old), new } if old.id == 1 && new.id == 4
+        ));
+    }
+
+    #[test]
+    #[cfg(any(
+        feature = "workspaces+river",
+        feature = "workspaces+niri",
+        feature = "workspaces+wayfire"
+    ))]
+    fn test_diff_workspaces_unchanged() {
+        let workspaces = [workspace(1, "1", "DP-1", Visibility::focused())];
+        assert!(diff_workspaces(&workspaces, &workspaces).is_empty());
+    }
+
+    #[test]
+    #[cfg(any(
+        feature = "workspaces+river",
+        feature = "workspaces+niri",
+        feature = "workspaces+wayfire"
+    ))]
+    fn test_diff_workspaces_unfocused_output_add_remove() {
+        let old = [
+            workspace(1, "1", "DP-1", Visibility::focused()),
+            workspace(2, "2", "HDMI-A-1", Visibility::visible()),
+            workspace(3, "3", "HDMI-A-1", Visibility::Hidden),
+        ];
+
+        let new = [
+            workspace(1, "1", "DP-1", Visibility::focused()),
+            workspace(2, "2", "HDMI-A-1", Visibility::visible()),
+            workspace(4, "4", "HDMI-A-1", Visibility::Hidden),
+        ];
+
+        let updates = diff_workspaces(&old, &new);
+        assert_eq!(updates.len(), 2);
+
+        assert!(matches!(updates[0], WorkspaceUpdate::Remove(3)));
+        assert!(matches!(&updates[1], WorkspaceUpdate::Add(w) if w.id == 4));
+    }
+
+    #[test]
+    #[cfg(any(
+        feature = "workspaces+river",
+        feature = "workspaces+niri",
+        feature = "workspaces+wayfire"
+    ))]
+    fn test_diff_workspaces_unfocused_output_visibility() {
+        let old = [
+            workspace(1, "1", "DP-1", Visibility::focused()),
+            workspace(2, "2", "HDMI-A-1", Visibility::visible()),
+            workspace(3, "3", "HDMI-A-1", Visibility::Hidden),
+        ];
+
+        let new = [
+            workspace(1, "1", "DP-1", Visibility::focused()),
+            workspace(2, "2", "HDMI-A-1", Visibility::Hidden),
+            workspace(3, "3", "HDMI-A-1", Visibility::visible()),
+        ];
+
+        let updates = diff_workspaces(&old, &new);
+        assert_eq!(updates.len(), 1);
+
+        let WorkspaceUpdate::Update(changed) = &updates[0] else {
+            panic!("expected update, got {:?}", updates[0]);
+        };
+
+        assert_eq!(
+            changed
+                .iter()
+                .map(|w| (w.id, w.visibility))
+                .collect::<Vec<_>>(),
+            vec![(2, Visibility::Hidden), (3, Visibility::visible())]
+        );
+    }
+
+    #[test]
+    #[cfg(any(
+        feature = "workspaces+river",
+        feature = "workspaces+niri",
+        feature = "workspaces+wayfire"
+    ))]
+    fn test_diff_workspaces_focus_with_visibility() {
+        let old = [
+            workspace(1, "1", "DP-1", Visibility::focused()),
+            workspace(2, "2", "HDMI-A-1", Visibility::visible()),
+            workspace(3, "3", "HDMI-A-1", Visibility::Hidden),
+        ];
+
+        // focus moves to the other output, and changes tag there
+        let new = [
+            workspace(1, "1", "DP-1", Visibility::visible()),
+            workspace(2, "2", "HDMI-A-1", Visibility::Hidden),
+            workspace(3, "3", "HDMI-A-1", Visibility::focused()),
+        ];
+
+        let updates = diff_workspaces(&old, &new);
+        assert_eq!(updates.len(), 2);
+
+        assert!(matches!(
+            &updates[0],
+            WorkspaceUpdate::Update(changed) if changed.len() == 1 && changed[0].id == 2
+        ));
+        assert!(matches!(
+            &updates[1],
+            WorkspaceUpdate::Focus { old: Some(old), new } if old.id == 1 && new.id == 3
+        ));
+    }
+
+    #[test]
+    fn test_visibility_across_monitors() {
+        let workspaces = [
+            workspace(1, "A", "DP-1", Visibility::focused()),
+            workspace(2, "B", "HDMI-A-1", Visibility::visible()),
+            workspace(3, "C", "HDMI-A-1", Visibility::Hidden),
+        ];
+
+        let states = workspaces
+            .iter()
+            .map(|w| {
+                (
+                    w.visibility.is_focused(),
+                    w.visibility.is_visible(),
+                    w.visibility.is_hidden(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            states,
+            vec![
+                (true, true, false),
+                (false, true, false),
+                (false, false, true)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filter_by_output() {
+        let workspaces = [
+            workspace(1, "1", "DP-1", Visibility::focused()),
+            workspace(2, "2", "HDMI-A-1", Visibility::visible()),
+            workspace(3, "3", "DP-1", Visibility::Hidden),
+            workspace(4, "4", "", Visibility::Hidden),
+        ];
+
+        let ids = |output| {
+            filter_by_output(&workspaces, output)
+                .iter()
+                .map(|w| w.id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(ids("DP-1"), vec![1, 3]);
+        assert_eq!(ids("HDMI-A-1"), vec![2]);
+        assert!(ids("").is_empty());
+    }
+
+    #[test]
+    fn test_sort_key_order() {
+        let mut keys = vec![
+            SortKey::Special(String::from("special:magic")),
+            SortKey::new(None, "web"),
+            SortKey::new(Some(10), "10"),
+            SortKey::new(None, "chat"),
+            SortKey::new(Some(2), "2: code"),
+        ];
+
+        keys.sort();
+
+        assert_eq!(
+            keys,
+            vec![
+                SortKey::Number(2),
+                SortKey::Number(10),
+                SortKey::Name(String::from("chat")),
+                SortKey::Name(String::from("web")),
+                SortKey::Special(String::from("special:magic")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rename_keeps_special_last() {
+        let key = SortKey::Special(String::from("special:magic"));
+        assert_eq!(
+            key.renamed(Some(1), "1"),
+            SortKey::Special(String::from("1"))
+        );
+
+        let key = SortKey::Number(1);
+        assert_eq!(key.renamed(None, "web"), SortKey::Name(String::from("web")));
+    }
+
+    #[test]
+    fn test_name_map_label() {
+        let map = WorkspaceNameMap(HashMap::from([
+            ("web".to_string(), "icon:firefox".to_string()),
+            ("2".to_string(), "two".to_string()),
+            ("code".to_string(), "text:icon:code".to_string()),
+        ]));
+
+        assert_eq!(
+            map.label("web", None),
+            WorkspaceLabel::Image("icon:firefox")
+        );
+        assert_eq!(map.label("2: chat", Some(2)), WorkspaceLabel::Text("two"));
+        assert_eq!(map.label("code", None), WorkspaceLabel::Text("icon:code"));
+        assert_eq!(map.label("3", Some(3)), WorkspaceLabel::Text("3"));
+    }
+
+    #[test]
+    fn test_parse_workspace_num() {
+        assert_eq!(parse_workspace_num("1"), Some(1));
+        assert_eq!(parse_workspace_num("10: web"), Some(10));
+        assert_eq!(parse_workspace_num("2web"), Some(2));
+        assert_eq!(parse_workspace_num("web"), None);
+        assert_eq!(parse_workspace_num("web 1"), None);
+        assert_eq!(parse_workspace_num(""), None);
+    }
+}
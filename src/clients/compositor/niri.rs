@@ -0,0 +1,328 @@
+use super::{diff_workspaces, SortKey, Visibility, Workspace, WorkspaceClient, WorkspaceUpdate};
+use crate::clients::reconnect::ReconnectPolicy;
+use crate::{arc_mut, await_sync, lock, send, spawn};
+use color_eyre::{Report, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::unix::OwnedReadHalf;
+use tokio::net::UnixStream;
+use tokio::sync::broadcast::{channel, Receiver, Sender};
+use tokio::time::sleep;
+use tracing::{debug, error, info, warn};
+
+/// Request sent over the niri IPC socket.
+#[derive(Debug, Serialize)]
+enum Request {
+    Workspaces,
+    EventStream,
+    Action(Action),
+}
+
+#[derive(Debug, Serialize)]
+enum Action {
+    FocusWorkspace { reference: WorkspaceReference },
+    FocusMonitor { output: String },
+}
+
+#[derive(Debug, Serialize)]
+enum WorkspaceReference {
+    Id(u64),
+    Index(u8),
+    Name(String),
+}
+
+#[derive(Debug, Deserialize)]
+enum Response {
+    Handled,
+    Workspaces(Vec<NiriWorkspace>),
+}
+
+/// Event received over the niri event stream.
+/// Only workspace events are handled, others are ignored.
+#[derive(Debug, Deserialize)]
+enum Event {
+    WorkspacesChanged { workspaces: Vec<NiriWorkspace> },
+    WorkspaceActivated { id: u64, focused: bool },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct NiriWorkspace {
+    id: u64,
+    idx: u8,
+    name: Option<String>,
+    output: Option<String>,
+    is_active: bool,
+    is_focused: bool,
+}
+
+impl From<&NiriWorkspace> for Workspace {
+    fn from(workspace: &NiriWorkspace) -> Self {
+        let visibility = match (workspace.is_focused, workspace.is_active) {
+            (true, _) => Visibility::focused(),
+            (false, true) => Visibility::visible(),
+            (false, false) => Visibility::Hidden,
+        };
+
+        // named workspaces keep their position in niri,
+        // so always order by index
+        let sort_key = SortKey::Number(i64::from(workspace.idx));
+
+        Self {
+            id: workspace.id as i64,
+            name: workspace
+                .name
+                .clone()
+                .unwrap_or_else(|| workspace.idx.to_string()),
+            num: Some(i32::from(workspace.idx)),
+            monitor: workspace.output.clone().unwrap_or_default(),
+            visibility,
+            urgent: false,
+            is_special: false,
+            sort_key,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Client {
+    socket_path: PathBuf,
+    reconnect_policy: ReconnectPolicy,
+    workspaces: Arc<Mutex<Vec<Workspace>>>,
+    workspace_tx: Sender<WorkspaceUpdate>,
+    _workspace_rx: Receiver<WorkspaceUpdate>,
+}
+
+impl Client {
+    pub(crate) fn new(reconnect_policy: ReconnectPolicy) -> Result<Self> {
+        let socket_path = std::env::var("NIRI_SOCKET")
+            .map(PathBuf::from)
+            .map_err(|_| Report::msg("NIRI_SOCKET is not set"))?;
+
+        let workspaces = match await_sync(request(&socket_path, &Request::Workspaces))? {
+            Response::Workspaces(workspaces) => workspaces.iter().map(Workspace::from).collect(),
+            Response::Handled => vec![],
+        };
+
+        info!("Niri IPC client connected");
+
+        let (workspace_tx, workspace_rx) = channel(16);
+
+        let client = Self {
+            socket_path,
+            reconnect_policy,
+            workspaces: arc_mut!(workspaces),
+            workspace_tx,
+            _workspace_rx: workspace_rx,
+        };
+
+        client.listen_workspace_events();
+        Ok(client)
+    }
+
+    fn listen_workspace_events(&self) {
+        let socket_path = self.socket_path.clone();
+        let workspaces = self.workspaces.clone();
+        let tx = self.workspace_tx.clone();
+        let reconnect_policy = self.reconnect_policy;
+
+        spawn(async move {
+            let mut backoff = reconnect_policy.backoff();
+
+            loop {
+                let started = Instant::now();
+
+                if let Err(err) = watch_events(&socket_path, &workspaces, &tx).await {
+                    error!("{err:?}");
+                }
+
+                // the stream was connected if it ran for a while,
+                // so start backing off from the beginning
+                if started.elapsed() > reconnect_policy.max_delay {
+                    backoff.reset();
+                }
+
+                // niri may have been restarted, so try to reconnect
+                let Some(delay) = backoff.next_delay() else {
+                    error!("Giving up reconnecting to Niri");
+                    break;
+                };
+
+                warn!(
+                    "Niri event stream closed, reconnecting in {}ms",
+                    delay.as_millis()
+                );
+                sleep(delay).await;
+            }
+        });
+    }
+}
+
+impl WorkspaceClient for Client {
+    fn focus(&self, id: i64) -> Result<()> {
+        let reference = WorkspaceReference::Id(u64::try_from(id)?);
+
+        let request = Request::Action(Action::FocusWorkspace { reference });
+        await_sync(request_handled(&self.socket_path, &request))
+    }
+
+    fn focus_by_name(&self, name: String) -> Result<()> {
+        // unnamed workspaces are named by index, which repeats across outputs,
+        // so prefer the one on the focused output
+        let output = lock!(self.workspaces)
+            .iter()
+            .find(|w| w.visibility.is_focused())
+            .map(|w| w.monitor.clone())
+            .unwrap_or_default();
+
+        self.focus_by_name_on_output(name, &output)
+    }
+
+    fn focus_by_name_on_output(&self, name: String, output: &str) -> Result<()> {
+        let (on_output, any) = {
+            let workspaces = lock!(self.workspaces);
+            let find = |on_output: bool| {
+                workspaces
+                    .iter()
+                    .find(|w| w.name == name && (!on_output || w.monitor == output))
+                    .map(|w| w.id as u64)
+            };
+
+            (find(true), find(false))
+        };
+
+        let reference = match (on_output, any, name.parse()) {
+            (Some(id), _, _) => WorkspaceReference::Id(id),
+            // an index refers to the focused output
+            (None, _, Ok(idx)) => {
+                if !output.is_empty() {
+                    let request = Request::Action(Action::FocusMonitor {
+                        output: output.to_string(),
+                    });
+                    await_sync(request_handled(&self.socket_path, &request))?;
+                }
+
+                WorkspaceReference::Index(idx)
+            }
+            (None, Some(id), Err(_)) => WorkspaceReference::Id(id),
+            (None, None, Err(_)) => WorkspaceReference::Name(name),
+        };
+
+        let request = Request::Action(Action::FocusWorkspace { reference });
+        await_sync(request_handled(&self.socket_path, &request))
+    }
+
+    fn rename(&self, _id: i64, _new_name: String) -> Result<()> {
+        Err(Report::msg("Renaming workspaces is not supported on Niri"))
+    }
+
+    fn move_workspace_to_output(&self, _id: i64, _output: String) -> Result<()> {
+        Err(Report::msg("Moving workspaces is not supported on Niri"))
+    }
+
+    fn subscribe_workspace_change(&self) -> Receiver<WorkspaceUpdate> {
+        let rx = self.workspace_tx.subscribe();
+
+        let workspaces = lock!(self.workspaces).clone();
+        send!(self.workspace_tx, WorkspaceUpdate::Init(workspaces));
+
+        rx
+    }
+}
+
+/// Opens a new connection to the socket and sends a single request,
+/// returning the stream's remaining lines along with the reply.
+async fn connect(
+    socket_path: &Path,
+    request: &Request,
+) -> Result<(Response, Lines<BufReader<OwnedReadHalf>>)> {
+    let stream = UnixStream::connect(socket_path).await?;
+    let (read, mut write) = stream.into_split();
+
+    let mut payload = serde_json::to_string(request)?;
+    payload.push('\n');
+    write.write_all(payload.as_bytes()).await?;
+
+    let mut lines = BufReader::new(read).lines();
+    let Some(line) = lines.next_line().await? else {
+        return Err(Report::msg("Niri closed the connection without replying"));
+    };
+
+    let response = serde_json::from_str::<Result<Response, String>>(&line)?
+        .map_err(|err| Report::msg(format!("Niri returned an error: {err}")))?;
+
+    Ok((response, lines))
+}
+
+async fn request(socket_path: &Path, request: &Request) -> Result<Response> {
+    connect(socket_path, request)
+        .await
+        .map(|(response, _)| response)
+}
+
+async fn request_handled(socket_path: &Path, request: &Request) -> Result<()> {
+    match self::request(socket_path, request).await? {
+        Response::Handled => Ok(()),
+        response => Err(Report::msg(format!("Unexpected response: {response:?}"))),
+    }
+}
+
+/// Listens on the event stream until it closes,
+/// sending workspace updates for each change.
+async fn watch_events(
+    socket_path: &Path,
+    workspaces: &Arc<Mutex<Vec<Workspace>>>,
+    tx: &Sender<WorkspaceUpdate>,
+) -> Result<()> {
+    let (_, mut lines) = connect(socket_path, &Request::EventStream).await?;
+
+    // the initial `WorkspacesChanged` event replaces this
+    let mut state: Vec<NiriWorkspace> = vec![];
+
+    while let Some(line) = lines.next_line().await? {
+        let Ok(event) = serde_json::from_str::<Event>(&line) else {
+            continue;
+        };
+
+        debug!("Received event: {event:?}");
+
+        match event {
+            Event::WorkspacesChanged { workspaces } => state = workspaces,
+            Event::WorkspaceActivated { id, focused } => activate(&mut state, id, focused),
+        }
+
+        let new = state.iter().map(Workspace::from).collect::<Vec<_>>();
+        let mut current = lock!(workspaces);
+
+        for update in diff_workspaces(&current, &new) {
+            debug!("Sending update: {update:?}");
+            send!(tx, update);
+        }
+
+        *current = new;
+    }
+
+    Ok(())
+}
+
+/// Marks a workspace as active on its output,
+/// and as the focused workspace if `focused` is set.
+fn activate(state: &mut [NiriWorkspace], id: u64, focused: bool) {
+    let Some(output) = state.iter().find(|w| w.id == id).map(|w| w.output.clone()) else {
+        return;
+    };
+
+    for workspace in state.iter_mut() {
+        let is_target = workspace.id == id;
+
+        if workspace.output == output {
+            workspace.is_active = is_target;
+        }
+
+        if focused {
+            workspace.is_focused = is_target;
+        }
+    }
+}
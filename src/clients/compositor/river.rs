@@ -0,0 +1,457 @@
+use self::protocol::control::zriver_command_callback_v1::{self, ZriverCommandCallbackV1};
+use self::protocol::control::zriver_control_v1::ZriverControlV1;
+use self::protocol::status::zriver_output_status_v1::{self, ZriverOutputStatusV1};
+use self::protocol::status::zriver_seat_status_v1::{self, ZriverSeatStatusV1};
+use self::protocol::status::zriver_status_manager_v1::ZriverStatusManagerV1;
+use super::{diff_workspaces, SortKey, Visibility, Workspace, WorkspaceClient, WorkspaceUpdate};
+use crate::{arc_mut, lock, send, spawn_blocking};
+use color_eyre::{Report, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::{channel, Receiver, Sender};
+use tracing::{debug, error, info};
+use wayland_client::globals::{registry_queue_init, GlobalListContents};
+use wayland_client::protocol::wl_output::{self, WlOutput};
+use wayland_client::protocol::wl_registry::{self, WlRegistry};
+use wayland_client::protocol::wl_seat::WlSeat;
+use wayland_client::{delegate_noop, Connection, Dispatch, Proxy, QueueHandle};
+
+/// Bindings generated from the river protocol definitions.
+#[allow(dead_code, unused_imports, clippy::all)]
+mod protocol {
+    pub mod status {
+        use wayland_client;
+        use wayland_client::protocol::*;
+
+        pub mod __interfaces {
+            use wayland_client::protocol::__interfaces::*;
+            wayland_scanner::generate_interfaces!("protocols/river-status-unstable-v1.xml");
+        }
+        use self::__interfaces::*;
+
+        wayland_scanner::generate_client_code!("protocols/river-status-unstable-v1.xml");
+    }
+
+    pub mod control {
+        use wayland_client;
+        use wayland_client::protocol::*;
+
+        pub mod __interfaces {
+            use wayland_client::protocol::__interfaces::*;
+            wayland_scanner::generate_interfaces!("protocols/river-control-unstable-v1.xml");
+        }
+        use self::__interfaces::*;
+
+        wayland_scanner::generate_client_code!("protocols/river-control-unstable-v1.xml");
+    }
+}
+
+/// River has a fixed set of 32 tags per output.
+const NUM_TAGS: u32 = 32;
+
+#[derive(Debug)]
+pub struct Client {
+    connection: Connection,
+    queue_handle: QueueHandle<State>,
+    control: ZriverControlV1,
+    seat: WlSeat,
+    workspaces: Arc<Mutex<Vec<Workspace>>>,
+    workspace_tx: Sender<WorkspaceUpdate>,
+    _workspace_rx: Receiver<WorkspaceUpdate>,
+}
+
+impl Client {
+    pub(crate) fn new() -> Result<Self> {
+        let connection = Connection::connect_to_env()?;
+        let (globals, mut queue) = registry_queue_init::<State>(&connection)?;
+        let queue_handle = queue.handle();
+
+        let status_manager: ZriverStatusManagerV1 = globals.bind(&queue_handle, 1..=4, ())?;
+        let control: ZriverControlV1 = globals.bind(&queue_handle, 1..=1, ())?;
+        let seat: WlSeat = globals.bind(&queue_handle, 1..=7, ())?;
+
+        info!("River status client connected");
+
+        status_manager.get_river_seat_status(&seat, &queue_handle, ());
+
+        let (workspace_tx, workspace_rx) = channel(16);
+        let workspaces = arc_mut!(vec![]);
+
+        let mut state = State {
+            status_manager,
+            outputs: HashMap::new(),
+            focused_output: None,
+            workspaces: vec![],
+            shared_workspaces: workspaces.clone(),
+            tx: workspace_tx.clone(),
+        };
+
+        globals.contents().with_list(|list| {
+            for global in list {
+                if global.interface == WlOutput::interface().name {
+                    state.add_output(
+                        globals.registry(),
+                        global.name,
+                        global.version,
+                        &queue_handle,
+                    );
+                }
+            }
+        });
+
+        // receive output names and initial tags before the first update
+        queue.roundtrip(&mut state)?;
+        state.update();
+
+        spawn_blocking(move || loop {
+            if let Err(err) = queue.blocking_dispatch(&mut state) {
+                error!("River event queue closed: {err:?}");
+                break;
+            }
+        });
+
+        Ok(Self {
+            connection,
+            queue_handle,
+            control,
+            seat,
+            workspaces,
+            workspace_tx,
+            _workspace_rx: workspace_rx,
+        })
+    }
+
+    /// Runs a river command on the seat,
+    /// in the same manner as `riverctl`.
+    fn run_river_command(&self, args: &[&str]) -> Result<()> {
+        for arg in args {
+            self.control.add_argument((*arg).to_string());
+        }
+
+        self.control.run_command(&self.seat, &self.queue_handle, ());
+        self.connection.flush()?;
+
+        Ok(())
+    }
+
+    /// Focuses a single tag on the focused output.
+    /// Tags are numbered from 1.
+    fn focus_tag(&self, tag: u32) -> Result<()> {
+        let tags = 1_u32 << (tag - 1);
+        self.run_river_command(&["set-focused-tags", &tags.to_string()])
+    }
+}
+
+/// Gets the workspace id for a tag index on the output with this global name.
+///
+/// Tags exist per-output, so the output is included in the id.
+fn workspace_id(global: u32, tag: u32) -> i64 {
+    i64::from(global) * i64::from(NUM_TAGS) + i64::from(tag)
+}
+
+/// Splits a workspace id back into the output global name and tag index.
+fn split_workspace_id(id: i64) -> Option<(u32, u32)> {
+    let global = u32::try_from(id.div_euclid(i64::from(NUM_TAGS))).ok()?;
+    let tag = u32::try_from(id.rem_euclid(i64::from(NUM_TAGS))).ok()?;
+    Some((global, tag))
+}
+
+/// Parses a tag number from a workspace name.
+/// Tags are numbered from 1.
+fn parse_tag(name: &str) -> Result<u32> {
+    name.parse::<u32>()
+        .ok()
+        .filter(|tag| (1..=NUM_TAGS).contains(tag))
+        .ok_or_else(|| Report::msg(format!("Invalid tag '{name}', expected 1-{NUM_TAGS}")))
+}
+
+impl WorkspaceClient for Client {
+    fn focus(&self, id: i64) -> Result<()> {
+        let (global, tag) =
+            split_workspace_id(id).ok_or_else(|| Report::msg(format!("Invalid tag id {id}")))?;
+
+        // every output has at least its focused tag listed,
+        // so any workspace from the same output gives its name
+        let output = lock!(self.workspaces)
+            .iter()
+            .find(|workspace| split_workspace_id(workspace.id).map(|(g, _)| g) == Some(global))
+            .map(|workspace| workspace.monitor.clone())
+            .ok_or_else(|| Report::msg(format!("No output for tag id {id}")))?;
+
+        // tags are set on the focused output, so focus the tag's output first
+        self.run_river_command(&["focus-output", &output])?;
+        self.focus_tag(tag + 1)
+    }
+
+    fn focus_by_name(&self, name: String) -> Result<()> {
+        self.focus_tag(parse_tag(&name)?)
+    }
+
+    fn focus_by_name_on_output(&self, name: String, output: &str) -> Result<()> {
+        let tag = parse_tag(&name)?;
+
+        // tags are set on the focused output, so focus the bar's output first
+        self.run_river_command(&["focus-output", output])?;
+        self.focus_tag(tag)
+    }
+
+    fn rename(&self, _id: i64, _new_name: String) -> Result<()> {
+        Err(Report::msg("River tags cannot be renamed"))
+    }
+
+    fn move_workspace_to_output(&self, _id: i64, _output: String) -> Result<()> {
+        Err(Report::msg("River tags cannot be moved between outputs"))
+    }
+
+    fn subscribe_workspace_change(&self) -> Receiver<WorkspaceUpdate> {
+        let rx = self.workspace_tx.subscribe();
+
+        let workspaces = lock!(self.workspaces).clone();
+        send!(self.workspace_tx, WorkspaceUpdate::Init(workspaces));
+
+        rx
+    }
+}
+
+#[derive(Debug)]
+struct OutputState {
+    output: WlOutput,
+    status: ZriverOutputStatusV1,
+    name: Option<String>,
+    focused_tags: u32,
+    view_tags: u32,
+}
+
+/// Event queue state,
+/// owned by the dispatch thread.
+#[derive(Debug)]
+struct State {
+    status_manager: ZriverStatusManagerV1,
+    /// Outputs, keyed by their global name.
+    outputs: HashMap<u32, OutputState>,
+    focused_output: Option<u32>,
+
+    /// Workspaces as of the last update.
+    workspaces: Vec<Workspace>,
+    shared_workspaces: Arc<Mutex<Vec<Workspace>>>,
+    tx: Sender<WorkspaceUpdate>,
+}
+
+impl State {
+    fn add_output(
+        &mut self,
+        registry: &WlRegistry,
+        name: u32,
+        version: u32,
+        queue_handle: &QueueHandle<Self>,
+    ) {
+        // v4 is required for the output name
+        let output = registry.bind::<WlOutput, _, _>(name, version.min(4), queue_handle, name);
+        let status = self
+            .status_manager
+            .get_river_output_status(&output, queue_handle, name);
+
+        self.outputs.insert(
+            name,
+            OutputState {
+                output,
+                status,
+                name: None,
+                focused_tags: 0,
+                view_tags: 0,
+            },
+        );
+    }
+
+    /// Builds a workspace for each occupied or focused tag on each output.
+    fn current_workspaces(&self) -> Vec<Workspace> {
+        let mut workspaces = BTreeMap::new();
+
+        for (&global, output) in &self.outputs {
+            let Some(monitor) = output.name.as_ref() else {
+                continue;
+            };
+
+            let is_focused_output = self.focused_output == Some(global);
+
+            for tag in 0..NUM_TAGS {
+                let mask = 1 << tag;
+                if (output.focused_tags | output.view_tags) & mask == 0 {
+                    continue;
+                }
+
+                let visibility = match (output.focused_tags & mask != 0, is_focused_output) {
+                    (true, true) => Visibility::focused(),
+                    (true, false) => Visibility::visible(),
+                    (false, _) => Visibility::Hidden,
+                };
+
+                let id = workspace_id(global, tag);
+                let num = tag as i32 + 1;
+
+                workspaces.insert(
+                    id,
+                    Workspace {
+                        id,
+                        name: num.to_string(),
+                        num: Some(num),
+                        monitor: monitor.clone(),
+                        visibility,
+                        urgent: false,
+                        is_special: false,
+                        sort_key: SortKey::Number(i64::from(num)),
+                    },
+                );
+            }
+        }
+
+        workspaces.into_values().collect()
+    }
+
+    /// Diffs the current tag state against the previous,
+    /// sending updates for any changes.
+    fn update(&mut self) {
+        let workspaces = self.current_workspaces();
+
+        for update in diff_workspaces(&self.workspaces, &workspaces) {
+            debug!("Sending update: {update:?}");
+            send!(self.tx, update);
+        }
+
+        lock!(self.shared_workspaces).clone_from(&workspaces);
+        self.workspaces = workspaces;
+    }
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for State {
+    fn event(
+        state: &mut Self,
+        registry: &WlRegistry,
+        event: wl_registry::Event,
+        _data: &GlobalListContents,
+        _conn: &Connection,
+        queue_handle: &QueueHandle<Self>,
+    ) {
+        match event {
+            wl_registry::Event::Global {
+                name,
+                interface,
+                version,
+            } if interface == WlOutput::interface().name => {
+                state.add_output(registry, name, version, queue_handle);
+            }
+            wl_registry::Event::GlobalRemove { name } => {
+                if let Some(output) = state.outputs.remove(&name) {
+                    output.status.destroy();
+
+                    // release was added in v3
+                    if output.output.version() >= 3 {
+                        output.output.release();
+                    }
+
+                    if state.focused_output == Some(name) {
+                        state.focused_output = None;
+                    }
+
+                    state.update();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlOutput, u32> for State {
+    fn event(
+        state: &mut Self,
+        _output: &WlOutput,
+        event: wl_output::Event,
+        global: &u32,
+        _conn: &Connection,
+        _queue_handle: &QueueHandle<Self>,
+    ) {
+        if let wl_output::Event::Name { name } = event {
+            if let Some(output) = state.outputs.get_mut(global) {
+                output.name = Some(name);
+                state.update();
+            }
+        }
+    }
+}
+
+impl Dispatch<ZriverOutputStatusV1, u32> for State {
+    fn event(
+        state: &mut Self,
+        _status: &ZriverOutputStatusV1,
+        event: zriver_output_status_v1::Event,
+        global: &u32,
+        _conn: &Connection,
+        _queue_handle: &QueueHandle<Self>,
+    ) {
+        let Some(output) = state.outputs.get_mut(global) else {
+            return;
+        };
+
+        match event {
+            zriver_output_status_v1::Event::FocusedTags { tags } => output.focused_tags = tags,
+            zriver_output_status_v1::Event::ViewTags { tags } => {
+                // array of native-endian u32 tag masks, one per view
+                output.view_tags = tags
+                    .chunks_exact(4)
+                    .map(|chunk| u32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                    .fold(0, |acc, tags| acc | tags);
+            }
+            _ => return,
+        }
+
+        state.update();
+    }
+}
+
+impl Dispatch<ZriverSeatStatusV1, ()> for State {
+    fn event(
+        state: &mut Self,
+        _status: &ZriverSeatStatusV1,
+        event: zriver_seat_status_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _queue_handle: &QueueHandle<Self>,
+    ) {
+        if let zriver_seat_status_v1::Event::FocusedOutput { output } = event {
+            state.focused_output = output.data::<u32>().copied();
+            state.update();
+        }
+    }
+}
+
+impl Dispatch<ZriverCommandCallbackV1, ()> for State {
+    fn event(
+        _state: &mut Self,
+        _callback: &ZriverCommandCallbackV1,
+        event: zriver_command_callback_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _queue_handle: &QueueHandle<Self>,
+    ) {
+        if let zriver_command_callback_v1::Event::Failure { failure_message } = event {
+            error!("River command failed: {failure_message}");
+        }
+    }
+}
+
+delegate_noop!(State: ZriverStatusManagerV1);
+delegate_noop!(State: ZriverControlV1);
+delegate_noop!(State: ignore WlSeat);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_workspace_id() {
+        assert_eq!(split_workspace_id(workspace_id(3, 0)), Some((3, 0)));
+        assert_eq!(split_workspace_id(workspace_id(3, 31)), Some((3, 31)));
+        assert_eq!(split_workspace_id(workspace_id(4, 5)), Some((4, 5)));
+        assert_eq!(split_workspace_id(-1), None);
+    }
+}
@@ -1,68 +1,763 @@
-use super::{Visibility, Workspace, WorkspaceClient, WorkspaceUpdate};
+use super::{
+    parse_workspace_num, FocusedWindow, MoveDirection, SortKey, Visibility, WindowClient,
+    Workspace, WorkspaceClient, WorkspaceUpdate, COMMAND_SUCCESS,
+};
+use crate::clients::reconnect::ReconnectPolicy;
+use crate::tasks::detached;
 use crate::{await_sync, send, spawn};
 use color_eyre::{Report, Result};
 use futures_lite::StreamExt;
-use std::sync::Arc;
-use swayipc_async::{Connection, Event, EventType, Node, WorkspaceChange, WorkspaceEvent};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use swayipc_async::{
+    BarConfig, Connection, Event, EventStream, EventType, InputChange, ModeEvent, Node, NodeType,
+    WindowChange, WindowEvent, WorkspaceChange, WorkspaceEvent,
+};
 use tokio::sync::broadcast::{channel, Receiver, Sender};
 use tokio::sync::Mutex;
-use tracing::{info, trace};
+use tokio::time::{sleep, timeout};
+use tracing::{error, info, trace, warn};
+
+/// Gets the focused window from a window event,
+/// if the event affects the focused window.
+fn focused_window(event: WindowEvent) -> Option<FocusedWindow> {
+    match event.change {
+        WindowChange::Focus => {}
+        WindowChange::Title if event.container.focused => {}
+        _ => return None,
+    }
+
+    Some(FocusedWindow::from(event.container))
+}
+
+impl From<Node> for FocusedWindow {
+    fn from(container: Node) -> Self {
+        // focus can move onto a parent container or workspace,
+        // which has no title of its own
+        let is_window = matches!(container.node_type, NodeType::Con | NodeType::FloatingCon)
+            && container.nodes.is_empty()
+            && container.floating_nodes.is_empty();
+
+        Self {
+            title: container.name.filter(|_| is_window).unwrap_or_default(),
+            app_id: container
+                .app_id
+                // xwayland windows have a class instead
+                .or_else(|| container.window_properties.and_then(|props| props.class))
+                .filter(|_| is_window),
+        }
+    }
+}
+
+/// A window on a workspace.
+#[derive(Debug, Clone)]
+pub struct WindowInfo {
+    pub id: i64,
+    /// The app id, for Wayland windows,
+    /// or the window class, for XWayland windows.
+    pub app_id: Option<String>,
+    pub title: Option<String>,
+}
+
+impl From<&Node> for WindowInfo {
+    fn from(node: &Node) -> Self {
+        let class = node
+            .window_properties
+            .as_ref()
+            .and_then(|props| props.class.clone());
+
+        Self {
+            id: node.id,
+            app_id: node.app_id.clone().or(class),
+            title: node.name.clone(),
+        }
+    }
+}
+
+/// A keyboard layout change.
+#[derive(Debug, Clone)]
+pub struct InputEvent {
+    /// The input device identifier, for example `1:1:AT_Translated_Set_2_keyboard`.
+    pub identifier: String,
+    /// The name of the now active layout, for example `English (US)`.
+    pub layout: String,
+}
+
+impl InputEvent {
+    /// Gets the new layout from an input event,
+    /// if the event is a keyboard layout change.
+    fn from_event(event: swayipc_async::InputEvent) -> Option<Self> {
+        if !matches!(event.change, InputChange::XkbLayout) || event.input.input_type != "keyboard" {
+            return None;
+        }
+
+        Some(Self {
+            identifier: event.input.identifier,
+            layout: event.input.xkb_active_layout_name?,
+        })
+    }
+}
+
+/// A sway output (monitor).
+#[derive(Debug, Clone)]
+pub struct OutputInfo {
+    pub name: String,
+    pub make: String,
+    pub model: String,
+    /// Whether the output is enabled.
+    pub active: bool,
+    /// The current resolution, as `(width, height)`.
+    /// This is `None` for inactive outputs.
+    pub resolution: Option<(i32, i32)>,
+}
+
+impl From<swayipc_async::Output> for OutputInfo {
+    fn from(output: swayipc_async::Output) -> Self {
+        Self {
+            name: output.name,
+            make: output.make,
+            model: output.model,
+            active: output.active,
+            resolution: output.current_mode.map(|mode| (mode.width, mode.height)),
+        }
+    }
+}
+
+/// Time to wait for further mark changes
+/// before sending the updated marks.
+const MARK_DEBOUNCE: Duration = Duration::from_millis(50);
 
 #[derive(Debug)]
 pub struct Client {
     client: Arc<Mutex<Connection>>,
+    /// Whether the workspace event stream is currently connected.
+    connected: Arc<AtomicBool>,
     workspace_tx: Sender<WorkspaceUpdate>,
     _workspace_rx: Receiver<WorkspaceUpdate>,
+    /// Used by event subscriptions when their connection is lost.
+    reconnect_policy: ReconnectPolicy,
+    /// Shared by all binding mode subscribers.
+    /// Created on first subscription.
+    mode_tx: OnceLock<Sender<ModeEvent>>,
+    /// Shared by all window focus subscribers.
+    /// Created on first subscription.
+    window_tx: OnceLock<Sender<FocusedWindow>>,
 }
 
 impl Client {
-    pub(crate) async fn new() -> Result<Self> {
+    pub(crate) async fn new(reconnect_policy: ReconnectPolicy) -> Result<Self> {
         // Avoid using `arc_mut!` here because we need tokio Mutex.
         let client = Arc::new(Mutex::new(Connection::new().await?));
         info!("Sway IPC subscription client connected");
 
         let (workspace_tx, workspace_rx) = channel(16);
 
+        // subscribe up front so that startup fails if sway is unreachable
+        let events = subscribe_workspace_events(&client, &workspace_tx, false).await?;
+        let connected = Arc::new(AtomicBool::new(true));
+
         {
-            // create 2nd client as subscription takes ownership
-            let client = Connection::new().await?;
+            let client = client.clone();
             let workspace_tx = workspace_tx.clone();
+            let connected = connected.clone();
 
             spawn(async move {
-                let event_types = [EventType::Workspace];
-                let mut events = client.subscribe(event_types).await?;
+                let mut events = Some(events);
+                let mut backoff = reconnect_policy.backoff();
 
-                while let Some(event) = events.next().await {
-                    trace!("event: {:?}", event);
-                    if let Event::Workspace(event) = event? {
-                        let event = WorkspaceUpdate::from(*event);
-                        if !matches!(event, WorkspaceUpdate::Unknown) {
-                            workspace_tx.send(event)?;
+                loop {
+                    if let Some(events) = events.take() {
+                        backoff.reset();
+
+                        match forward_workspace_events(events, &client, &workspace_tx).await {
+                            Ok(()) => warn!("Sway IPC event stream closed"),
+                            Err(err) => error!("Sway IPC event stream failed: {err:?}"),
                         }
+
+                        connected.store(false, Ordering::Relaxed);
+                    }
+
+                    // sway may be restarting, so back off between attempts
+                    let Some(delay) = backoff.next_delay() else {
+                        error!("Giving up reconnecting to Sway IPC");
+                        break;
                     };
-                }
 
-                Ok::<(), Report>(())
+                    info!("Reconnecting to Sway IPC in {}ms", delay.as_millis());
+                    sleep(delay).await;
+
+                    match subscribe_workspace_events(&client, &workspace_tx, true).await {
+                        Ok(new_events) => {
+                            info!("Reconnected to Sway IPC");
+                            connected.store(true, Ordering::Relaxed);
+                            events = Some(new_events);
+                        }
+                        Err(err) => error!("Failed to reconnect to Sway IPC: {err:?}"),
+                    }
+                }
             });
         }
 
         Ok(Self {
             client,
+            connected,
             workspace_tx,
             _workspace_rx: workspace_rx,
+            reconnect_policy,
+            mode_tx: OnceLock::new(),
+            window_tx: OnceLock::new(),
         })
     }
+
+    /// Creates a new receiver for binding mode changes.
+    ///
+    /// All receivers share a single subscription to sway,
+    /// which is created on first use and kept for the lifetime of the client.
+    #[allow(dead_code)] // not yet used by any module
+    pub fn subscribe_mode(&self) -> Receiver<ModeEvent> {
+        self.mode_tx
+            .get_or_init(|| {
+                let (tx, rx) = channel(16);
+                let reconnect_policy = self.reconnect_policy;
+
+                {
+                    let tx = tx.clone();
+
+                    // shared by every subscriber, so not stopped along with the first one
+                    detached(|| {
+                        spawn(async move {
+                            // keeps the subscription alive between subscribers
+                            let _rx = rx;
+
+                            keep_subscribed(
+                                reconnect_policy,
+                                &[EventType::Mode],
+                                tx,
+                                |mut events, tx| async move {
+                                    while let Some(event) = events.next().await {
+                                        if let Event::Mode(event) = event? {
+                                            tx.send(event)?;
+                                        }
+                                    }
+
+                                    Ok::<(), Report>(())
+                                },
+                            )
+                            .await;
+                        })
+                    });
+                }
+
+                tx
+            })
+            .subscribe()
+    }
+
+    /// Creates a new receiver for tick events,
+    /// as sent by `swaymsg -t send_tick <payload>`.
+    ///
+    /// Each tick's payload is delivered unmodified.
+    #[allow(dead_code)] // not yet used by any module
+    pub fn subscribe_tick(&self) -> Receiver<String> {
+        let (tx, rx) = channel(16);
+
+        spawn(keep_subscribed(
+            self.reconnect_policy,
+            &[EventType::Tick],
+            tx,
+            |mut events, tx| async move {
+                while let Some(event) = events.next().await {
+                    // sway sends an empty tick on subscribing, which is skipped
+                    if let Event::Tick(event) = event? {
+                        if !event.first {
+                            tx.send(event.payload)?;
+                        }
+                    }
+                }
+
+                Ok::<(), Report>(())
+            },
+        ));
+
+        rx
+    }
+
+    /// Creates a new receiver for keyboard layout changes.
+    ///
+    /// Only keyboard inputs are included;
+    /// other input events are ignored.
+    #[allow(dead_code)] // not yet used by any module
+    pub fn subscribe_input(&self) -> Receiver<InputEvent> {
+        let (tx, rx) = channel(16);
+
+        spawn(keep_subscribed(
+            self.reconnect_policy,
+            &[EventType::Input],
+            tx,
+            |mut events, tx| async move {
+                while let Some(event) = events.next().await {
+                    if let Event::Input(event) = event? {
+                        if let Some(event) = InputEvent::from_event(*event) {
+                            tx.send(event)?;
+                        }
+                    }
+                }
+
+                Ok::<(), Report>(())
+            },
+        ));
+
+        rx
+    }
+
+    /// Creates a new receiver for the name of the focused output.
+    ///
+    /// The current output is sent immediately,
+    /// then again each time focus moves onto a different output.
+    #[allow(dead_code)] // not yet used by any module
+    pub fn subscribe_focused_output(&self) -> Receiver<String> {
+        let (tx, rx) = channel(16);
+        let client = self.client.clone();
+
+        spawn(keep_subscribed(
+            self.reconnect_policy,
+            &[EventType::Window, EventType::Workspace],
+            tx,
+            move |mut events, tx| {
+                let client = client.clone();
+
+                async move {
+                    // sent again after reconnecting, as focus may have moved
+                    let mut current = None;
+
+                    loop {
+                        let tree = client.lock().await.get_tree().await?;
+                        let output = focused_output(&tree);
+
+                        if output.is_some() && output != current {
+                            current.clone_from(&output);
+                            tx.send(output.unwrap_or_default())?;
+                        }
+
+                        match events.next().await {
+                            Some(event) => {
+                                event?;
+                            }
+                            None => break,
+                        }
+                    }
+
+                    Ok::<(), Report>(())
+                }
+            },
+        ));
+
+        rx
+    }
+
+    /// Focuses the workspace with this number,
+    /// creating it if it does not exist.
+    ///
+    /// Unlike focusing by name, this matches workspaces such as `1: web`
+    /// by their number, rather than only one named exactly `1`.
+    #[allow(dead_code)] // not yet used by any module
+    pub fn focus_number(&self, number: i32) -> Result<()> {
+        self.run_command_checked(focus_number_command(number))
+    }
+
+    /// Shows the next window from the scratchpad,
+    /// or hides the focused scratchpad window.
+    #[allow(dead_code)] // not yet used by any module
+    pub fn scratchpad_toggle(&self) -> Result<()> {
+        self.run_command_checked(String::from("scratchpad show"))
+    }
+
+    /// Toggles the scratchpad window(s) matching the sway criteria,
+    /// for example `app_id="dropdown"`.
+    #[allow(dead_code)] // not yet used by any module
+    pub fn scratchpad_show(&self, criteria: String) -> Result<()> {
+        self.run_command_checked(format!("[{criteria}] scratchpad show"))
+    }
+
+    /// Gets the connected outputs.
+    /// Inactive (disabled) outputs are only included if `include_inactive` is set.
+    #[allow(dead_code)] // not yet used by any module
+    pub fn outputs(&self, include_inactive: bool) -> Result<Vec<OutputInfo>> {
+        await_sync(async move {
+            let mut client = self.client.lock().await;
+            get_outputs(&mut client, include_inactive).await
+        })
+    }
+
+    /// Creates a new receiver for output changes.
+    ///
+    /// The full list of active outputs is sent
+    /// each time an output is added, removed or reconfigured.
+    #[allow(dead_code)] // not yet used by any module
+    pub fn subscribe_output_change(&self) -> Receiver<Vec<OutputInfo>> {
+        let (tx, rx) = channel(16);
+        let client = self.client.clone();
+
+        spawn(keep_subscribed(
+            self.reconnect_policy,
+            &[EventType::Output],
+            tx,
+            move |mut events, tx| {
+                let client = client.clone();
+
+                async move {
+                    while let Some(event) = events.next().await {
+                        if let Event::Output(_) = event? {
+                            let mut client = client.lock().await;
+                            tx.send(get_outputs(&mut client, false).await?)?;
+                        }
+                    }
+
+                    Ok::<(), Report>(())
+                }
+            },
+        ));
+
+        rx
+    }
+
+    /// Gets the full layout tree,
+    /// from the root node down to each window.
+    #[allow(dead_code)] // not yet used by any module
+    pub fn get_tree(&self) -> Result<Node> {
+        await_sync(async move {
+            let mut client = self.client.lock().await;
+            Ok(client.get_tree().await?)
+        })
+    }
+
+    /// Gets the names of all marks currently set on windows.
+    #[allow(dead_code)] // not yet used by any module
+    pub fn get_marks(&self) -> Result<Vec<String>> {
+        await_sync(async move {
+            let mut client = self.client.lock().await;
+            Ok(client.get_marks().await?)
+        })
+    }
+
+    /// Gets the config for the `swaybar` with this id.
+    #[allow(dead_code)] // not yet used by any module
+    pub fn get_bar_config(&self, id: &str) -> Result<BarConfig> {
+        await_sync(async move {
+            let mut client = self.client.lock().await;
+            Ok(client.get_bar_config(id).await?)
+        })
+    }
+
+    /// Creates a new receiver for window mark changes.
+    ///
+    /// The full set of marks is sent each time it changes.
+    /// Changes in quick succession are sent as a single update.
+    #[allow(dead_code)] // not yet used by any module
+    pub fn subscribe_marks(&self) -> Receiver<Vec<String>> {
+        let (tx, rx) = channel(16);
+        let client = self.client.clone();
+
+        spawn(keep_subscribed(
+            self.reconnect_policy,
+            &[EventType::Window],
+            tx,
+            move |mut events, tx| {
+                let client = client.clone();
+
+                async move {
+                    // sent again after reconnecting, as marks may have changed
+                    let mut current = client.lock().await.get_marks().await?;
+                    tx.send(current.clone())?;
+
+                    while let Some(event) = events.next().await {
+                        if !is_mark_event(&event?) {
+                            continue;
+                        }
+
+                        // wait for any further changes to settle
+                        while let Ok(Some(event)) = timeout(MARK_DEBOUNCE, events.next()).await {
+                            event?;
+                        }
+
+                        let marks = client.lock().await.get_marks().await?;
+                        if marks != current {
+                            current.clone_from(&marks);
+                            tx.send(marks)?;
+                        }
+                    }
+
+                    Ok::<(), Report>(())
+                }
+            },
+        ));
+
+        rx
+    }
+
+    /// Gets the windows on the workspace with this id,
+    /// including floating windows.
+    #[allow(dead_code)] // not yet used by any module
+    pub fn workspace_windows(&self, id: i64) -> Result<Vec<WindowInfo>> {
+        let tree = self.get_tree()?;
+
+        let workspace = find_node(&tree, id)
+            .filter(|node| node.node_type == NodeType::Workspace)
+            .ok_or_else(|| Report::msg(format!("No workspace with id {id}")))?;
+
+        let mut windows = vec![];
+        collect_windows(workspace, &mut windows);
+
+        Ok(windows)
+    }
+
+    /// Runs a sway command,
+    /// returning an error if sway rejects it.
+    fn run_command_checked(&self, command: String) -> Result<()> {
+        await_sync(async move {
+            let mut client = self.client.lock().await;
+
+            for result in client.run_command(command).await? {
+                result?;
+            }
+
+            Ok::<(), Report>(())
+        })
+    }
+}
+
+impl WindowClient for Client {
+    fn subscribe_window_focus(&self) -> Receiver<FocusedWindow> {
+        let tx = self.window_tx.get_or_init(|| {
+            let (tx, rx) = channel(16);
+            let reconnect_policy = self.reconnect_policy;
+            let client = self.client.clone();
+
+            {
+                let tx = tx.clone();
+
+                // shared by every subscriber, so not stopped along with the first one
+                detached(|| {
+                    spawn(async move {
+                        // keeps the subscription alive between subscribers
+                        let _rx = rx;
+
+                        keep_subscribed(
+                            reconnect_policy,
+                            &[EventType::Window],
+                            tx,
+                            move |mut events, tx| {
+                                let client = client.clone();
+
+                                async move {
+                                    // focus may have moved while disconnected
+                                    let tree = client.lock().await.get_tree().await?;
+                                    tx.send(focused_tree_window(tree))?;
+
+                                    while let Some(event) = events.next().await {
+                                        if let Event::Window(event) = event? {
+                                            if let Some(window) = focused_window(*event) {
+                                                tx.send(window)?;
+                                            }
+                                        }
+                                    }
+
+                                    Ok::<(), Report>(())
+                                }
+                            },
+                        )
+                        .await;
+                    })
+                });
+            }
+
+            tx
+        });
+
+        let rx = tx.subscribe();
+
+        let tree = await_sync(async { self.client.lock().await.get_tree().await });
+        match tree {
+            Ok(tree) => send!(tx, focused_tree_window(tree)),
+            Err(err) => error!("Failed to get focused window: {err:?}"),
+        }
+
+        rx
+    }
 }
 
 impl WorkspaceClient for Client {
-    fn focus(&self, id: String) -> Result<()> {
+    fn focus(&self, id: i64) -> Result<()> {
         await_sync(async move {
             let mut client = self.client.lock().await;
-            client.run_command(format!("workspace {id}")).await
+
+            // sway can only focus by name, so look the current one up first
+            let name = client
+                .get_workspaces()
+                .await?
+                .into_iter()
+                .find(|workspace| workspace.id == id)
+                .map(|workspace| workspace.name)
+                .ok_or_else(|| Report::msg(format!("No workspace with id {id}")))?;
+
+            client.run_command(focus_command(&name)).await?;
+
+            Ok::<(), Report>(())
+        })
+    }
+
+    fn focus_by_name(&self, name: String) -> Result<()> {
+        await_sync(async move {
+            let mut client = self.client.lock().await;
+            client.run_command(focus_command(&name)).await
         })?;
         Ok(())
     }
 
+    fn rename(&self, id: i64, new_name: String) -> Result<()> {
+        await_sync(async move {
+            let mut client = self.client.lock().await;
+
+            // sway can only rename by name, so look the current one up first
+            let name = client
+                .get_workspaces()
+                .await?
+                .into_iter()
+                .find(|workspace| workspace.id == id)
+                .map(|workspace| workspace.name)
+                .ok_or_else(|| Report::msg(format!("No workspace with id {id}")))?;
+
+            let command = format!("rename workspace {} to {}", quote(&name), quote(&new_name));
+
+            for result in client.run_command(command).await? {
+                result?;
+            }
+
+            Ok::<(), Report>(())
+        })
+    }
+
+    fn move_workspace_to_output(&self, id: i64, output: String) -> Result<()> {
+        await_sync(async move {
+            let mut client = self.client.lock().await;
+
+            let outputs = client.get_outputs().await?;
+            if !outputs.iter().any(|o| o.name == output) {
+                let names = outputs
+                    .iter()
+                    .map(|o| o.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                return Err(Report::msg(format!(
+                    "Unknown output '{output}', expected one of: {names}"
+                )));
+            }
+
+            let name = client
+                .get_workspaces()
+                .await?
+                .into_iter()
+                .find(|workspace| workspace.id == id)
+                .map(|workspace| workspace.name)
+                .ok_or_else(|| Report::msg(format!("No workspace with id {id}")))?;
+
+            // sway can only move the focused workspace
+            let command = format!(
+                "workspace {}; move workspace to output {}",
+                quote(&name),
+                quote(&output)
+            );
+
+            for result in client.run_command(command).await? {
+                result?;
+            }
+
+            Ok::<(), Report>(())
+        })
+    }
+
+    fn move_workspace_position(&self, id: i64, direction: MoveDirection) -> Result<()> {
+        await_sync(async move {
+            let mut client = self.client.lock().await;
+            let workspaces = client.get_workspaces().await?;
+
+            let workspace = workspaces
+                .iter()
+                .find(|workspace| workspace.id == id)
+                .ok_or_else(|| Report::msg(format!("No workspace with id {id}")))?;
+
+            // sway orders workspaces by number, so moving one means renumbering it
+            if workspace.num < 0 {
+                return Err(Report::msg(format!(
+                    "Workspace '{}' has no number, so cannot be moved",
+                    workspace.name
+                )));
+            }
+
+            let target = match direction {
+                MoveDirection::Left => workspace.num - 1,
+                MoveDirection::Right => workspace.num + 1,
+            };
+
+            if target < 0 {
+                return Err(Report::msg(format!(
+                    "Workspace '{}' is already first",
+                    workspace.name
+                )));
+            }
+
+            let rename =
+                |from: &str, to: &str| format!("rename workspace {} to {}", quote(from), quote(to));
+
+            let new_name = renumber(&workspace.name, target);
+
+            let command = match workspaces.iter().find(|other| other.num == target) {
+                // swap with the neighbour, via a temporary name to avoid a clash
+                Some(other) => {
+                    let temp_name = format!("ironbar-move-{}", other.id);
+                    [
+                        rename(&other.name, &temp_name),
+                        rename(&workspace.name, &new_name),
+                        rename(&temp_name, &renumber(&other.name, workspace.num)),
+                    ]
+                    .join("; ")
+                }
+                None => rename(&workspace.name, &new_name),
+            };
+
+            for result in client.run_command(command).await? {
+                result?;
+            }
+
+            Ok::<(), Report>(())
+        })
+    }
+
+    fn run_command(&self, command: String) -> Result<Vec<String>> {
+        await_sync(async move {
+            let mut client = self.client.lock().await;
+
+            let outcomes = client
+                .run_command(command)
+                .await?
+                .into_iter()
+                .map(|result| match result {
+                    Ok(()) => COMMAND_SUCCESS.to_string(),
+                    Err(err) => err.to_string(),
+                })
+                .collect::<Vec<_>>();
+
+            Ok::<_, Report>(outcomes)
+        })
+    }
+
+    fn is_healthy(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
     fn subscribe_workspace_change(&self) -> Receiver<WorkspaceUpdate> {
         let rx = self.workspace_tx.subscribe();
 
@@ -85,15 +780,304 @@ impl WorkspaceClient for Client {
     }
 }
 
+/// Subscribes to sway events on a new connection,
+/// passing each event stream to `forward` along with a sender for `tx`.
+///
+/// When the stream ends, for example because sway restarted,
+/// this resubscribes under the reconnect policy.
+/// It stops once all receivers for `tx` have been dropped.
+async fn keep_subscribed<T, F, Fut>(
+    reconnect_policy: ReconnectPolicy,
+    event_types: &[EventType],
+    tx: Sender<T>,
+    mut forward: F,
+) where
+    F: FnMut(EventStream, Sender<T>) -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    let mut backoff = reconnect_policy.backoff();
+
+    loop {
+        let res = match Connection::new().await {
+            // subscription takes ownership of the connection
+            Ok(client) => match client.subscribe(event_types).await {
+                Ok(events) => {
+                    backoff.reset();
+                    forward(events, tx.clone()).await
+                }
+                Err(err) => Err(err.into()),
+            },
+            Err(err) => Err(err.into()),
+        };
+
+        // sending fails once there are no receivers left
+        if tx.receiver_count() == 0 {
+            break;
+        }
+
+        match res {
+            Ok(()) => warn!("Sway IPC event stream closed"),
+            Err(err) => error!("Sway IPC event stream failed: {err:?}"),
+        }
+
+        let Some(delay) = backoff.next_delay() else {
+            error!("Giving up resubscribing to Sway IPC events");
+            break;
+        };
+
+        info!(
+            "Resubscribing to Sway IPC events in {}ms",
+            delay.as_millis()
+        );
+        sleep(delay).await;
+    }
+}
+
+/// Creates a new subscription to workspace events.
+///
+/// When `resync` is set, the command connection is also replaced,
+/// and a `WorkspaceUpdate::Init` with the current workspaces is sent,
+/// as any previous state may be stale.
+async fn subscribe_workspace_events(
+    client: &Mutex<Connection>,
+    tx: &Sender<WorkspaceUpdate>,
+    resync: bool,
+) -> Result<EventStream> {
+    // a separate connection is required as subscribing takes ownership
+    let events = Connection::new()
+        .await?
+        .subscribe([EventType::Workspace])
+        .await?;
+
+    if resync {
+        let mut client = client.lock().await;
+        *client = Connection::new().await?;
+
+        let workspaces = client.get_workspaces().await?;
+        tx.send(WorkspaceUpdate::Init(
+            workspaces.into_iter().map(Workspace::from).collect(),
+        ))?;
+    }
+
+    Ok(events)
+}
+
+/// Forwards workspace events to subscribers
+/// until the stream closes or errors.
+async fn forward_workspace_events(
+    mut events: EventStream,
+    client: &Mutex<Connection>,
+    tx: &Sender<WorkspaceUpdate>,
+) -> Result<()> {
+    while let Some(event) = events.next().await {
+        trace!("event: {:?}", event);
+        if let Event::Workspace(event) = event? {
+            let mut event = WorkspaceUpdate::from(*event);
+
+            if let WorkspaceUpdate::Focus { old, new } = &mut event {
+                let needs_monitor =
+                    old.as_ref().is_some_and(|w| w.monitor.is_empty()) || new.monitor.is_empty();
+
+                if needs_monitor {
+                    let mut client = client.lock().await;
+                    if let Err(err) = resolve_focus_monitors(&mut client, old.as_mut(), new).await {
+                        error!("Failed to resolve focus event outputs: {err:?}");
+                    }
+                }
+            }
+
+            if !matches!(event, WorkspaceUpdate::Unknown) {
+                tx.send(event)?;
+            }
+        };
+    }
+
+    Ok(())
+}
+
+async fn get_outputs(client: &mut Connection, include_inactive: bool) -> Result<Vec<OutputInfo>> {
+    let outputs = client
+        .get_outputs()
+        .await?
+        .into_iter()
+        .filter(|output| include_inactive || output.active)
+        .map(OutputInfo::from)
+        .collect();
+
+    Ok(outputs)
+}
+
+/// Gets the focused window from the tree,
+/// or an empty window if no window is focused.
+fn focused_tree_window(tree: Node) -> FocusedWindow {
+    tree.find_focused(|node| node.focused)
+        .map(FocusedWindow::from)
+        .unwrap_or_default()
+}
+
+/// Finds the node with this id in the tree.
+fn find_node(node: &Node, id: i64) -> Option<&Node> {
+    if node.id == id {
+        return Some(node);
+    }
+
+    node.nodes
+        .iter()
+        .chain(&node.floating_nodes)
+        .find_map(|node| find_node(node, id))
+}
+
+/// Adds each window below the node to `windows`.
+///
+/// Windows are the leaves of the tree,
+/// with the containers above them only used for layout.
+fn collect_windows(node: &Node, windows: &mut Vec<WindowInfo>) {
+    let is_window = matches!(node.node_type, NodeType::Con | NodeType::FloatingCon)
+        && node.nodes.is_empty()
+        && node.floating_nodes.is_empty();
+
+    if is_window {
+        windows.push(WindowInfo::from(node));
+    }
+
+    for child in node.nodes.iter().chain(&node.floating_nodes) {
+        collect_windows(child, windows);
+    }
+}
+
+/// Whether the event is a window being marked or unmarked.
+fn is_mark_event(event: &Event) -> bool {
+    matches!(event, Event::Window(event) if matches!(event.change, WindowChange::Mark))
+}
+
+/// Gets the name of the output containing the focused node.
+///
+/// Floating windows can span several outputs,
+/// so the output containing the window's center is used for those.
+fn focused_output(tree: &Node) -> Option<String> {
+    fn find_focused(node: &Node) -> Option<&Node> {
+        if node.focused {
+            return Some(node);
+        }
+
+        node.nodes
+            .iter()
+            .chain(&node.floating_nodes)
+            .find_map(find_focused)
+    }
+
+    // the root's children are the outputs,
+    // plus a hidden output holding the scratchpad
+    let outputs = tree.nodes.iter().filter(|output| {
+        output.node_type == NodeType::Output && output.name.as_deref() != Some("__i3")
+    });
+
+    for output in outputs.clone() {
+        let Some(focused) = find_focused(output) else {
+            continue;
+        };
+
+        if focused.node_type != NodeType::FloatingCon {
+            return output.name.clone();
+        }
+
+        let rect = &focused.rect;
+        let (x, y) = (rect.x + rect.width / 2, rect.y + rect.height / 2);
+
+        return outputs
+            .clone()
+            .find(|output| {
+                let rect = &output.rect;
+                (rect.x..rect.x + rect.width).contains(&x)
+                    && (rect.y..rect.y + rect.height).contains(&y)
+            })
+            .unwrap_or(output)
+            .name
+            .clone();
+    }
+
+    None
+}
+
+/// Fills in the monitor for focus event workspaces
+/// which sway sent without an output.
+///
+/// This happens for the old workspace in some cases,
+/// for example when it is emptied and destroyed by the focus change.
+async fn resolve_focus_monitors(
+    client: &mut Connection,
+    old: Option<&mut Workspace>,
+    new: &mut Workspace,
+) -> Result<()> {
+    let workspaces = client.get_workspaces().await?;
+
+    let find_output = |id: i64| {
+        workspaces
+            .iter()
+            .find(|w| w.id == id)
+            .map(|w| w.output.clone())
+    };
+
+    if new.monitor.is_empty() {
+        if let Some(output) = find_output(new.id) {
+            new.monitor = output;
+        }
+    }
+
+    if let Some(old) = old.filter(|w| w.monitor.is_empty()) {
+        // a destroyed workspace will have been on the same output,
+        // as sway only destroys it when focus moves away within that output
+        old.monitor = find_output(old.id).unwrap_or_else(|| new.monitor.clone());
+    }
+
+    Ok(())
+}
+
+/// Gets the command to focus the workspace with this exact name.
+fn focus_command(name: &str) -> String {
+    format!("workspace {}", quote(name))
+}
+
+/// Gets the command to focus the workspace with this number,
+/// whatever the rest of its name.
+fn focus_number_command(number: i32) -> String {
+    format!("workspace number {number}")
+}
+
+/// Replaces the number at the start of a workspace name,
+/// keeping the rest of the name.
+fn renumber(name: &str, num: i32) -> String {
+    let end = name
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(name.len());
+
+    format!("{num}{}", &name[end..])
+}
+
+/// Wraps a workspace name in quotes,
+/// so that it is parsed as a single argument by sway.
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
 impl From<Node> for Workspace {
     fn from(node: Node) -> Self {
         let visibility = Visibility::from(&node);
+        let name = node.name.unwrap_or_default();
+        let num = node
+            .num
+            .filter(|&num| num >= 0)
+            .or_else(|| parse_workspace_num(&name));
 
         Self {
             id: node.id,
-            name: node.name.unwrap_or_default(),
+            num,
+            sort_key: SortKey::new(num, &name),
+            name,
             monitor: node.output.unwrap_or_default(),
             visibility,
+            urgent: node.urgent,
+            is_special: false,
         }
     }
 }
@@ -101,12 +1085,18 @@ impl From<Node> for Workspace {
 impl From<swayipc_async::Workspace> for Workspace {
     fn from(workspace: swayipc_async::Workspace) -> Self {
         let visibility = Visibility::from(&workspace);
+        // sway uses -1 for workspaces without a number
+        let num = Some(workspace.num).filter(|&num| num >= 0);
 
         Self {
             id: workspace.id,
+            num,
+            sort_key: SortKey::new(num, &workspace.name),
             name: workspace.name,
             monitor: workspace.output,
             visibility,
+            urgent: workspace.urgent,
+            is_special: false,
         }
     }
 }
@@ -137,21 +1127,106 @@ impl From<&swayipc_async::Workspace> for Visibility {
 
 impl From<WorkspaceEvent> for WorkspaceUpdate {
     fn from(event: WorkspaceEvent) -> Self {
-        match event.change {
-            WorkspaceChange::Init => {
-                Self::Add(event.current.expect("Missing current workspace").into())
-            }
-            WorkspaceChange::Empty => {
-                Self::Remove(event.current.expect("Missing current workspace").id)
-            }
-            WorkspaceChange::Focus => Self::Focus {
+        match (event.change, event.current) {
+            (WorkspaceChange::Init, Some(current)) => Self::Add(current.into()),
+            (WorkspaceChange::Empty, Some(current)) => Self::Remove(current.id),
+            (WorkspaceChange::Focus, Some(current)) => Self::Focus {
                 old: event.old.map(Workspace::from),
-                new: Workspace::from(event.current.expect("Missing current workspace")),
+                new: Workspace::from(current),
+            },
+            (WorkspaceChange::Move, Some(current)) => Self::Move(current.into()),
+            (WorkspaceChange::Rename, Some(current)) => Self::Rename {
+                id: current.id,
+                name: current.name.unwrap_or_default(),
+            },
+            (WorkspaceChange::Urgent, Some(current)) => Self::Urgent {
+                id: current.id,
+                urgent: current.urgent,
             },
-            WorkspaceChange::Move => {
-                Self::Move(event.current.expect("Missing current workspace").into())
+            (
+                change @ (WorkspaceChange::Init
+                | WorkspaceChange::Empty
+                | WorkspaceChange::Focus
+                | WorkspaceChange::Move
+                | WorkspaceChange::Rename
+                | WorkspaceChange::Urgent),
+                None,
+            ) => {
+                warn!("Received workspace {change:?} event with no current workspace, ignoring");
+                Self::Unknown
             }
             _ => Self::Unknown,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event_without_current(change: &str) -> WorkspaceEvent {
+        let json = format!(r#"{{"change":"{change}","current":null,"old":null}}"#);
+        serde_json::from_str(&json).expect("valid workspace event")
+    }
+
+    #[test]
+    fn test_missing_current_does_not_panic() {
+        for change in [
+            "init", "empty", "focus", "move", "rename", "urgent", "reload",
+        ] {
+            let update = WorkspaceUpdate::from(event_without_current(change));
+            assert!(
+                matches!(update, WorkspaceUpdate::Unknown),
+                "expected unknown update for '{change}'"
+            );
+        }
+    }
+
+    #[test]
+    fn test_renumber() {
+        assert_eq!(renumber("1", 2), "2");
+        assert_eq!(renumber("10: web", 9), "9: web");
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("1: web"), r#""1: web""#);
+        assert_eq!(quote(r#"a "b" \c"#), r#""a \"b\" \\c""#);
+    }
+
+    #[test]
+    fn test_focus_commands() {
+        assert_eq!(focus_command("1"), r#"workspace "1""#);
+        assert_eq!(focus_command("1: web"), r#"workspace "1: web""#);
+        assert_eq!(focus_number_command(1), "workspace number 1");
+    }
+
+    fn sway_workspace(name: &str, output: &str, focused: bool, visible: bool) -> Workspace {
+        let json = format!(
+            r#"{{
+                "id": 1, "num": 1, "name": "{name}", "layout": "splith",
+                "visible": {visible}, "focused": {focused}, "urgent": false,
+                "representation": null, "orientation": "horizontal",
+                "rect": {{ "x": 0, "y": 0, "width": 1920, "height": 1080 }},
+                "output": "{output}", "focus": []
+            }}"#
+        );
+
+        serde_json::from_str::<swayipc_async::Workspace>(&json)
+            .expect("valid workspace")
+            .into()
+    }
+
+    #[test]
+    fn test_visibility_across_outputs() {
+        let a = sway_workspace("A", "DP-1", true, true);
+        assert!(a.visibility.is_focused());
+
+        let b = sway_workspace("B", "HDMI-A-1", false, true);
+        assert!(b.visibility.is_visible());
+        assert!(!b.visibility.is_focused());
+
+        let c = sway_workspace("C", "HDMI-A-1", false, false);
+        assert!(c.visibility.is_hidden());
+    }
+}
@@ -0,0 +1,338 @@
+use super::{diff_workspaces, SortKey, Visibility, Workspace, WorkspaceClient, WorkspaceUpdate};
+use crate::clients::reconnect::ReconnectPolicy;
+use crate::{arc_mut, await_sync, lock, send, spawn};
+use color_eyre::{Report, Result};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+use tokio::sync::broadcast::{channel, Receiver, Sender};
+use tokio::time::sleep;
+use tracing::{debug, error, info, warn};
+
+/// Events which may change the workspace state.
+const WATCHED_EVENTS: [&str; 4] = [
+    "wset-workspace-changed",
+    "output-gain-focus",
+    "output-added",
+    "output-removed",
+];
+
+/// Workspace ids are made up of the output id and workspace index,
+/// with this many ids set aside for each output.
+const IDS_PER_OUTPUT: i64 = 1 << 16;
+
+#[derive(Debug, Clone, Deserialize)]
+struct Output {
+    id: u32,
+    name: String,
+    workspace: OutputWorkspace,
+}
+
+/// The output's workspace grid,
+/// and the position of its current workspace on it.
+#[derive(Debug, Clone, Copy, Deserialize)]
+struct OutputWorkspace {
+    x: i32,
+    y: i32,
+    grid_width: i32,
+    grid_height: i32,
+}
+
+#[derive(Debug, Deserialize)]
+struct FocusedOutput {
+    info: Option<Output>,
+}
+
+impl Output {
+    /// Gets a workspace for each position on the output's grid.
+    ///
+    /// Wayfire workspaces are unnamed,
+    /// so they are named by their number, counting across each row in turn.
+    fn workspaces(&self, is_focused: bool) -> Vec<Workspace> {
+        let grid = self.workspace;
+
+        (0..grid.grid_height)
+            .flat_map(|y| (0..grid.grid_width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let index = y * grid.grid_width + x;
+
+                let visibility = match ((x, y) == (grid.x, grid.y), is_focused) {
+                    (true, true) => Visibility::focused(),
+                    (true, false) => Visibility::visible(),
+                    (false, _) => Visibility::Hidden,
+                };
+
+                Workspace {
+                    id: i64::from(self.id) * IDS_PER_OUTPUT + i64::from(index),
+                    name: (index + 1).to_string(),
+                    num: Some(index + 1),
+                    monitor: self.name.clone(),
+                    visibility,
+                    urgent: false,
+                    is_special: false,
+                    sort_key: SortKey::Number(i64::from(index + 1)),
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub struct Client {
+    socket_path: PathBuf,
+    reconnect_policy: ReconnectPolicy,
+    workspaces: Arc<Mutex<Vec<Workspace>>>,
+    workspace_tx: Sender<WorkspaceUpdate>,
+    _workspace_rx: Receiver<WorkspaceUpdate>,
+}
+
+impl Client {
+    pub(crate) fn new(reconnect_policy: ReconnectPolicy) -> Result<Self> {
+        let socket_path = std::env::var("WAYFIRE_SOCKET")
+            .map(PathBuf::from)
+            .map_err(|_| Report::msg("WAYFIRE_SOCKET is not set"))?;
+
+        let workspaces = await_sync(current_workspaces(&socket_path))?;
+
+        info!("Wayfire IPC client connected");
+
+        let (workspace_tx, workspace_rx) = channel(16);
+
+        let client = Self {
+            socket_path,
+            reconnect_policy,
+            workspaces: arc_mut!(workspaces),
+            workspace_tx,
+            _workspace_rx: workspace_rx,
+        };
+
+        client.listen_workspace_events();
+        Ok(client)
+    }
+
+    fn listen_workspace_events(&self) {
+        let socket_path = self.socket_path.clone();
+        let workspaces = self.workspaces.clone();
+        let tx = self.workspace_tx.clone();
+        let reconnect_policy = self.reconnect_policy;
+
+        spawn(async move {
+            let mut backoff = reconnect_policy.backoff();
+
+            loop {
+                let started = Instant::now();
+
+                if let Err(err) = watch_events(&socket_path, &workspaces, &tx).await {
+                    error!("{err:?}");
+                }
+
+                // the stream was connected if it ran for a while,
+                // so start backing off from the beginning
+                if started.elapsed() > reconnect_policy.max_delay {
+                    backoff.reset();
+                }
+
+                let Some(delay) = backoff.next_delay() else {
+                    error!("Giving up reconnecting to Wayfire");
+                    break;
+                };
+
+                warn!(
+                    "Wayfire event stream closed, reconnecting in {}ms",
+                    delay.as_millis()
+                );
+                sleep(delay).await;
+            }
+        });
+    }
+}
+
+impl WorkspaceClient for Client {
+    fn focus(&self, id: i64) -> Result<()> {
+        let output_id = id / IDS_PER_OUTPUT;
+        let index = i32::try_from(id % IDS_PER_OUTPUT)?;
+
+        await_sync(async {
+            let outputs: Vec<Output> =
+                request(&self.socket_path, "window-rules/list-outputs", json!({})).await?;
+
+            let output = outputs
+                .into_iter()
+                .find(|output| i64::from(output.id) == output_id)
+                .ok_or_else(|| Report::msg(format!("No output for workspace {id}")))?;
+
+            let data = json!({
+                "x": index % output.workspace.grid_width,
+                "y": index / output.workspace.grid_width,
+                "output-id": output.id,
+            });
+
+            request::<Value>(&self.socket_path, "vswitch/set-workspace", data).await?;
+            Ok::<(), Report>(())
+        })
+    }
+
+    fn focus_by_name(&self, name: String) -> Result<()> {
+        let id = {
+            let workspaces = lock!(self.workspaces);
+
+            // each output has its own set of names,
+            // so prefer the workspace on the focused output
+            let focused_monitor = workspaces
+                .iter()
+                .find(|w| w.visibility.is_focused())
+                .map(|w| w.monitor.clone());
+
+            workspaces
+                .iter()
+                .filter(|w| w.name == name)
+                .max_by_key(|w| Some(&w.monitor) == focused_monitor.as_ref())
+                .map(|w| w.id)
+                .ok_or_else(|| Report::msg(format!("No workspace named '{name}'")))?
+        };
+
+        self.focus(id)
+    }
+
+    fn rename(&self, _id: i64, _new_name: String) -> Result<()> {
+        Err(Report::msg(
+            "Renaming workspaces is not supported on Wayfire",
+        ))
+    }
+
+    fn move_workspace_to_output(&self, _id: i64, _output: String) -> Result<()> {
+        Err(Report::msg("Moving workspaces is not supported on Wayfire"))
+    }
+
+    fn subscribe_workspace_change(&self) -> Receiver<WorkspaceUpdate> {
+        let rx = self.workspace_tx.subscribe();
+
+        let workspaces = lock!(self.workspaces).clone();
+        send!(self.workspace_tx, WorkspaceUpdate::Init(workspaces));
+
+        rx
+    }
+}
+
+/// Sends a message to the socket.
+/// Messages are JSON, prefixed with their length as a little-endian `u32`.
+async fn write_message(stream: &mut UnixStream, method: &str, data: Value) -> Result<()> {
+    let payload = serde_json::to_vec(&json!({ "method": method, "data": data }))?;
+    let len = u32::try_from(payload.len())?;
+
+    stream.write_all(&len.to_le_bytes()).await?;
+    stream.write_all(&payload).await?;
+
+    Ok(())
+}
+
+/// Reads the next message from the socket,
+/// returning an error if it is an error response.
+async fn read_message(stream: &mut UnixStream) -> Result<Value> {
+    let len = stream.read_u32_le().await?;
+
+    let mut payload = vec![0; len as usize];
+    stream.read_exact(&mut payload).await?;
+
+    let message: Value = serde_json::from_slice(&payload)?;
+
+    match message.get("error") {
+        Some(err) => Err(Report::msg(format!("Wayfire returned an error: {err}"))),
+        None => Ok(message),
+    }
+}
+
+/// Opens a new connection to the socket and sends a single request,
+/// returning the reply.
+async fn request<T: DeserializeOwned>(socket_path: &Path, method: &str, data: Value) -> Result<T> {
+    let mut stream = UnixStream::connect(socket_path).await?;
+
+    write_message(&mut stream, method, data).await?;
+    let response = read_message(&mut stream).await?;
+
+    Ok(serde_json::from_value(response)?)
+}
+
+/// Gets the workspaces across all outputs.
+async fn current_workspaces(socket_path: &Path) -> Result<Vec<Workspace>> {
+    let outputs: Vec<Output> = request(socket_path, "window-rules/list-outputs", json!({})).await?;
+
+    let focused: FocusedOutput =
+        request(socket_path, "window-rules/get-focused-output", json!({})).await?;
+    let focused_id = focused.info.map(|output| output.id);
+
+    Ok(outputs
+        .iter()
+        .flat_map(|output| output.workspaces(Some(output.id) == focused_id))
+        .collect())
+}
+
+/// Listens on the event stream until it closes,
+/// sending workspace updates for each change.
+async fn watch_events(
+    socket_path: &Path,
+    workspaces: &Arc<Mutex<Vec<Workspace>>>,
+    tx: &Sender<WorkspaceUpdate>,
+) -> Result<()> {
+    let mut stream = UnixStream::connect(socket_path).await?;
+
+    write_message(
+        &mut stream,
+        "window-rules/events/watch",
+        json!({ "events": WATCHED_EVENTS }),
+    )
+    .await?;
+    read_message(&mut stream).await?;
+
+    loop {
+        let event = read_message(&mut stream).await?;
+        debug!("Received event: {event}");
+
+        // events only include the changed output,
+        // so re-read the full state rather than track each change
+        let new = current_workspaces(socket_path).await?;
+        let mut current = lock!(workspaces);
+
+        for update in diff_workspaces(&current, &new) {
+            debug!("Sending update: {update:?}");
+            send!(tx, update);
+        }
+
+        *current = new;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_workspaces() {
+        let output = Output {
+            id: 2,
+            name: String::from("DP-1"),
+            workspace: OutputWorkspace {
+                x: 1,
+                y: 1,
+                grid_width: 2,
+                grid_height: 2,
+            },
+        };
+
+        let workspaces = output.workspaces(false);
+        assert_eq!(workspaces.len(), 4);
+
+        let last = &workspaces[3];
+        assert_eq!(last.id, 2 * IDS_PER_OUTPUT + 3);
+        assert_eq!(last.name, "4");
+        assert!(last.visibility.is_visible());
+        assert!(!last.visibility.is_focused());
+
+        assert!(!workspaces[0].visibility.is_visible());
+    }
+}
@@ -0,0 +1,66 @@
+use color_eyre::Result;
+use futures_lite::stream::{Stream, StreamExt};
+use std::collections::HashMap;
+use tracing::warn;
+use zbus::fdo::PropertiesProxy;
+use zbus::names::InterfaceName;
+use zbus::zvariant::{OwnedValue, Value};
+
+/// Watches for changes to the named properties of an interface on the proxy's object.
+///
+/// The stream yields the subset of `properties` included in each `PropertiesChanged` signal,
+/// skipping signals which do not change any of them.
+pub async fn watch_properties<'a>(
+    proxy: &PropertiesProxy<'a>,
+    interface: InterfaceName<'static>,
+    properties: &'static [&'static str],
+) -> Result<impl Stream<Item = HashMap<String, OwnedValue>> + 'a> {
+    let stream = proxy.receive_properties_changed().await?;
+
+    Ok(stream.filter_map(move |signal| {
+        let args = match signal.args() {
+            Ok(args) => args,
+            Err(err) => {
+                warn!("Invalid PropertiesChanged signal: {err:?}");
+                return None;
+            }
+        };
+
+        if args.interface_name != interface {
+            return None;
+        }
+
+        let changed = filter_properties(args.changed_properties, properties);
+        (!changed.is_empty()).then_some(changed)
+    }))
+}
+
+/// Takes the named properties out of a set of changed properties.
+fn filter_properties(
+    changed: HashMap<&str, Value>,
+    properties: &[&str],
+) -> HashMap<String, OwnedValue> {
+    changed
+        .into_iter()
+        .filter(|(name, _)| properties.contains(name))
+        .map(|(name, value)| (name.to_string(), OwnedValue::from(value)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_properties() {
+        let changed = HashMap::from([
+            ("Percentage", Value::from(50.0)),
+            ("Model", Value::from("battery")),
+        ]);
+
+        let filtered = filter_properties(changed, &["Percentage", "State"]);
+
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered["Percentage"].downcast_ref::<f64>(), Some(&50.0));
+    }
+}
@@ -1,5 +1,10 @@
+use crate::tasks::detached;
 use crate::{await_sync, Ironbar};
 use color_eyre::Result;
+#[cfg(any(feature = "workspaces+sway", feature = "workspaces+hyprland"))]
+use color_eyre::{Help, Report};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 use std::path::Path;
 use std::rc::Rc;
 use std::sync::Arc;
@@ -8,10 +13,14 @@ use std::sync::Arc;
 pub mod clipboard;
 #[cfg(feature = "workspaces")]
 pub mod compositor;
+#[cfg(feature = "upower")]
+pub mod dbus;
 #[cfg(feature = "cairo")]
 pub mod lua;
 #[cfg(feature = "music")]
 pub mod music;
+#[cfg(feature = "workspaces")]
+pub mod reconnect;
 #[cfg(feature = "notifications")]
 pub mod swaync;
 #[cfg(feature = "tray")]
@@ -29,6 +38,8 @@ pub struct Clients {
     wayland: Option<Arc<wayland::Client>>,
     #[cfg(feature = "workspaces")]
     workspaces: Option<Arc<dyn compositor::WorkspaceClient>>,
+    #[cfg(any(feature = "workspaces+sway", feature = "workspaces+hyprland"))]
+    windows: Option<Arc<dyn compositor::WindowClient>>,
     #[cfg(feature = "clipboard")]
     clipboard: Option<Arc<clipboard::Client>>,
     #[cfg(feature = "cairo")]
@@ -43,18 +54,79 @@ pub struct Clients {
     upower: Option<Arc<zbus::fdo::PropertiesProxy<'static>>>,
     #[cfg(feature = "volume")]
     volume: Option<Arc<volume::Client>>,
+
+    /// Used by compositor clients when their connection is lost.
+    #[cfg(feature = "workspaces")]
+    reconnect_policies: reconnect::ReconnectPolicies,
+
+    /// Errors from clients which failed to be created,
+    /// keyed by client name.
+    errors: HashMap<&'static str, String>,
+}
+
+/// The state of a single client,
+/// as reported by [`Clients::status`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientState {
+    /// No module has requested the client yet.
+    NotStarted,
+    /// The client was created and is connected.
+    Connected,
+    /// The client was created, but does not track whether it is still connected.
+    Unknown,
+    /// The client was created, but has since lost its connection.
+    Disconnected,
+    /// The client could not be created.
+    Failed(String),
+}
+
+impl ClientState {
+    /// Gets the state of a created client
+    /// which can report whether it is still connected.
+    #[cfg(feature = "workspaces")]
+    const fn from_health(healthy: bool) -> Self {
+        if healthy {
+            Self::Connected
+        } else {
+            Self::Disconnected
+        }
+    }
+}
+
+impl Display for ClientState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotStarted => write!(f, "not started"),
+            Self::Connected => write!(f, "connected"),
+            Self::Unknown => write!(f, "unknown"),
+            Self::Disconnected => write!(f, "disconnected"),
+            Self::Failed(err) => write!(f, "failed ({err})"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ClientStatus {
+    pub name: &'static str,
+    pub state: ClientState,
 }
 
 pub type ClientResult<T> = Result<Arc<T>>;
 
 impl Clients {
-    pub(crate) fn new() -> Self {
-        Self::default()
+    pub(crate) fn new(
+        #[cfg(feature = "workspaces")] reconnect_policies: reconnect::ReconnectPolicies,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "workspaces")]
+            reconnect_policies,
+            ..Self::default()
+        }
     }
 
     pub fn wayland(&mut self) -> Arc<wayland::Client> {
         self.wayland
-            .get_or_insert_with(|| Arc::new(wayland::Client::new()))
+            .get_or_insert_with(|| detached(|| Arc::new(wayland::Client::new())))
             .clone()
     }
 
@@ -63,7 +135,7 @@ impl Clients {
         let wayland = self.wayland();
 
         self.clipboard
-            .get_or_insert_with(|| Arc::new(clipboard::Client::new(wayland)))
+            .get_or_insert_with(|| detached(|| Arc::new(clipboard::Client::new(wayland))))
             .clone()
     }
 
@@ -71,16 +143,40 @@ impl Clients {
     pub fn workspaces(&mut self) -> ClientResult<dyn compositor::WorkspaceClient> {
         let client = match &self.workspaces {
             Some(workspaces) => workspaces.clone(),
-            None => {
-                let client = compositor::Compositor::create_workspace_client()?;
-                self.workspaces.replace(client.clone());
-                client
-            }
+            None => self.compositor()?.workspaces,
         };
 
         Ok(client)
     }
 
+    /// Gets the window client, which shares its instance with the workspace client.
+    #[cfg(any(feature = "workspaces+sway", feature = "workspaces+hyprland"))]
+    pub fn windows(&mut self) -> ClientResult<dyn compositor::WindowClient> {
+        if self.workspaces.is_none() {
+            self.compositor()?;
+        }
+
+        self.windows.clone().ok_or_else(|| {
+            Report::new(compositor::UnsupportedCompositorError)
+                .note("Currently window focus is only supported by Sway and Hyprland")
+        })
+    }
+
+    /// Creates the clients for the current compositor,
+    /// storing each for later use.
+    #[cfg(feature = "workspaces")]
+    fn compositor(&mut self) -> Result<compositor::CompositorClients> {
+        let clients = detached(|| compositor::Compositor::create_clients(&self.reconnect_policies))
+            .map_err(|err| self.record_error("workspaces", err))?;
+
+        self.workspaces.replace(clients.workspaces.clone());
+
+        #[cfg(any(feature = "workspaces+sway", feature = "workspaces+hyprland"))]
+        self.windows.clone_from(&clients.windows);
+
+        Ok(clients)
+    }
+
     #[cfg(feature = "cairo")]
     pub fn lua(&mut self, config_dir: &Path) -> Rc<lua::LuaEngine> {
         self.lua
@@ -92,7 +188,7 @@ impl Clients {
     pub fn music(&mut self, client_type: music::ClientType) -> Arc<dyn music::MusicClient> {
         self.music
             .entry(client_type.clone())
-            .or_insert_with(|| music::create_client(client_type))
+            .or_insert_with(|| detached(|| music::create_client(client_type)))
             .clone()
     }
 
@@ -101,7 +197,8 @@ impl Clients {
         let client = match &self.notifications {
             Some(client) => client.clone(),
             None => {
-                let client = await_sync(async { swaync::Client::new().await })?;
+                let client = detached(|| await_sync(async { swaync::Client::new().await }))
+                    .map_err(|err| self.record_error("notifications", err))?;
                 let client = Arc::new(client);
                 self.notifications.replace(client.clone());
                 client
@@ -118,7 +215,9 @@ impl Clients {
             None => {
                 let service_name = format!("{}-{}", env!("CARGO_CRATE_NAME"), Ironbar::unique_id());
 
-                let client = await_sync(async { tray::Client::new(&service_name).await })?;
+                let client =
+                    detached(|| await_sync(async { tray::Client::new(&service_name).await }))
+                        .map_err(|err| self.record_error("tray", err))?;
                 let client = Arc::new(client);
                 self.tray.replace(client.clone());
                 client
@@ -132,7 +231,7 @@ impl Clients {
     pub fn upower(&mut self) -> Arc<zbus::fdo::PropertiesProxy<'static>> {
         self.upower
             .get_or_insert_with(|| {
-                crate::await_sync(async { upower::create_display_proxy().await })
+                detached(|| await_sync(async { upower::create_display_proxy().await }))
             })
             .clone()
     }
@@ -140,9 +239,81 @@ impl Clients {
     #[cfg(feature = "volume")]
     pub fn volume(&mut self) -> Arc<volume::Client> {
         self.volume
-            .get_or_insert_with(volume::create_client)
+            .get_or_insert_with(|| detached(volume::create_client))
             .clone()
     }
+
+    /// Gets the state of each client,
+    /// for diagnosing why a module may not be showing anything.
+    pub fn status(&self) -> Vec<ClientStatus> {
+        let mut status = vec![self.client_status(
+            "wayland",
+            self.wayland.as_ref().map(|_| ClientState::Unknown),
+        )];
+
+        #[cfg(feature = "clipboard")]
+        status.push(self.client_status(
+            "clipboard",
+            self.clipboard.as_ref().map(|_| ClientState::Unknown),
+        ));
+
+        #[cfg(feature = "workspaces")]
+        status.push(
+            self.client_status(
+                "workspaces",
+                self.workspaces
+                    .as_ref()
+                    .map(|client| ClientState::from_health(client.is_healthy())),
+            ),
+        );
+
+        #[cfg(feature = "music")]
+        status.push(self.client_status(
+            "music",
+            (!self.music.is_empty()).then_some(ClientState::Unknown),
+        ));
+
+        #[cfg(feature = "notifications")]
+        status.push(self.client_status(
+            "notifications",
+            self.notifications.as_ref().map(|_| ClientState::Unknown),
+        ));
+
+        #[cfg(feature = "tray")]
+        status.push(self.client_status("tray", self.tray.as_ref().map(|_| ClientState::Unknown)));
+
+        #[cfg(feature = "upower")]
+        status
+            .push(self.client_status("upower", self.upower.as_ref().map(|_| ClientState::Unknown)));
+
+        #[cfg(feature = "volume")]
+        status
+            .push(self.client_status("volume", self.volume.as_ref().map(|_| ClientState::Unknown)));
+
+        status
+    }
+
+    /// Gets the status of a client,
+    /// given its state if it has been created.
+    fn client_status(&self, name: &'static str, state: Option<ClientState>) -> ClientStatus {
+        let state = state.unwrap_or_else(|| {
+            self.errors
+                .get(name)
+                .map_or(ClientState::NotStarted, |err| {
+                    ClientState::Failed(err.clone())
+                })
+        });
+
+        ClientStatus { name, state }
+    }
+
+    /// Stores the error from a client which failed to be created,
+    /// so it can be included in the status.
+    #[cfg(any(feature = "workspaces", feature = "notifications", feature = "tray"))]
+    fn record_error(&mut self, name: &'static str, err: color_eyre::Report) -> color_eyre::Report {
+        self.errors.insert(name, err.to_string());
+        err
+    }
 }
 
 /// Types implementing this trait
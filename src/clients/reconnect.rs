@@ -0,0 +1,210 @@
+use crate::config::ReconnectConfig;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// Controls how a client reconnects after losing its connection.
+///
+/// The delay between attempts doubles after each failure,
+/// up to `max_delay`, with some random jitter added
+/// so that several clients do not all retry at once.
+#[derive(Debug, Clone, Copy)]
+pub struct ReconnectPolicy {
+    /// The number of failed attempts before giving up.
+    /// `None` retries forever.
+    pub max_retries: Option<u32>,
+    /// The delay before the first attempt.
+    pub base_delay: Duration,
+    /// The longest delay between attempts.
+    pub max_delay: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: None,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl From<&ReconnectConfig> for ReconnectPolicy {
+    fn from(config: &ReconnectConfig) -> Self {
+        Self {
+            max_retries: config.max_retries,
+            base_delay: Duration::from_millis(config.base_delay),
+            max_delay: Duration::from_millis(config.max_delay),
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Creates a new backoff, starting from the first attempt.
+    pub fn backoff(self) -> Backoff {
+        Backoff {
+            policy: self,
+            attempt: 0,
+        }
+    }
+
+    /// Gets the delay before the attempt with this index,
+    /// excluding jitter.
+    fn delay(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2_u32.saturating_pow(attempt))
+            .min(self.max_delay)
+    }
+}
+
+/// The reconnect policy to use for each client.
+///
+/// Clients use the default policy,
+/// unless there is an override for their name.
+#[derive(Debug, Clone, Default)]
+pub struct ReconnectPolicies {
+    default: ReconnectPolicy,
+    /// Keyed by lowercase client name.
+    overrides: HashMap<String, ReconnectPolicy>,
+}
+
+impl From<ReconnectConfig> for ReconnectPolicies {
+    fn from(config: ReconnectConfig) -> Self {
+        let overrides = config
+            .clients
+            .iter()
+            .map(|(name, config)| (name.to_lowercase(), ReconnectPolicy::from(config)))
+            .collect();
+
+        Self {
+            default: ReconnectPolicy::from(&config),
+            overrides,
+        }
+    }
+}
+
+impl ReconnectPolicies {
+    /// Gets the policy for the client with this name.
+    pub fn get(&self, client: &str) -> ReconnectPolicy {
+        self.overrides
+            .get(&client.to_lowercase())
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+/// Tracks the attempts made under a `ReconnectPolicy`.
+#[derive(Debug)]
+pub struct Backoff {
+    policy: ReconnectPolicy,
+    attempt: u32,
+}
+
+impl Backoff {
+    /// Gets how long to wait before the next attempt,
+    /// or `None` if the policy's retries are used up.
+    pub fn next_delay(&mut self) -> Option<Duration> {
+        if self
+            .policy
+            .max_retries
+            .is_some_and(|max_retries| self.attempt >= max_retries)
+        {
+            return None;
+        }
+
+        let delay = self.policy.delay(self.attempt);
+        self.attempt = self.attempt.saturating_add(1);
+
+        Some(delay + jitter(delay))
+    }
+
+    /// Starts again from the first attempt.
+    /// This should be called once connected.
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+}
+
+/// Gets a random duration of up to a quarter of `delay`.
+fn jitter(delay: Duration) -> Duration {
+    let max = delay.as_millis() / 4;
+    if max == 0 {
+        return Duration::ZERO;
+    }
+
+    // each `RandomState` is seeded randomly,
+    // which avoids pulling in an RNG just for this
+    let random = RandomState::new().build_hasher().finish();
+    Duration::from_millis((u128::from(random) % max) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_doubles_up_to_max() {
+        let policy = ReconnectPolicy {
+            max_retries: None,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(5),
+        };
+
+        let delays = (0..5).map(|attempt| policy.delay(attempt).as_secs());
+        assert_eq!(delays.collect::<Vec<_>>(), vec![1, 2, 4, 5, 5]);
+    }
+
+    #[test]
+    fn test_next_delay_adds_jitter() {
+        let mut backoff = ReconnectPolicy::default().backoff();
+
+        let delay = backoff.next_delay().expect("to get delay");
+        assert!(delay >= Duration::from_millis(500));
+        assert!(delay < Duration::from_millis(625));
+    }
+
+    #[test]
+    fn test_max_retries() {
+        let mut backoff = ReconnectPolicy {
+            max_retries: Some(2),
+            ..ReconnectPolicy::default()
+        }
+        .backoff();
+
+        assert!(backoff.next_delay().is_some());
+        assert!(backoff.next_delay().is_some());
+        assert!(backoff.next_delay().is_none());
+
+        backoff.reset();
+        assert!(backoff.next_delay().is_some());
+    }
+
+    #[test]
+    fn test_client_override() {
+        let config = ReconnectConfig {
+            max_retries: Some(3),
+            base_delay: 500,
+            max_delay: 30_000,
+            clients: HashMap::from([(
+                String::from("sway"),
+                ReconnectConfig {
+                    max_retries: None,
+                    base_delay: 100,
+                    max_delay: 1000,
+                    clients: HashMap::new(),
+                },
+            )]),
+        };
+
+        let policies = ReconnectPolicies::from(config);
+
+        let sway = policies.get("Sway");
+        assert_eq!(sway.max_retries, None);
+        assert_eq!(sway.base_delay, Duration::from_millis(100));
+
+        let hyprland = policies.get("Hyprland");
+        assert_eq!(hyprland.max_retries, Some(3));
+        assert_eq!(hyprland.base_delay, Duration::from_millis(500));
+    }
+}
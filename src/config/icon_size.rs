@@ -0,0 +1,186 @@
+use gtk::pango;
+use gtk::prelude::*;
+use serde::{Deserialize, Deserializer};
+use std::str::FromStr;
+
+/// Font size in points used for `em` sizes
+/// if the widget has no font set.
+const DEFAULT_FONT_SIZE_PT: f64 = 10.0;
+
+/// Screen resolution used when GDK does not report one.
+const DEFAULT_DPI: f64 = 96.0;
+
+/// The size to render an icon at.
+///
+/// This can be set as a plain integer number of pixels,
+/// or as a string with a `px`, `pt` or `em` unit.
+/// `em` sizes are relative to the widget's font size.
+///
+/// Sizes are resolved when a module builds its widgets,
+/// before they are added to the bar and styled,
+/// so `em` sizes follow the default GTK font rather than any stylesheet `font-size`.
+///
+/// # Example
+///
+/// ```corn
+/// { icon_size = 24 }
+/// { icon_size = "24px" }
+/// { icon_size = "1.5em" }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IconSize {
+    Pixels(i32),
+    Points(f64),
+    Em(f64),
+}
+
+impl IconSize {
+    /// Resolves the size to logical pixels,
+    /// using the font and screen resolution of `widget`.
+    pub fn resolve<W: IsA<gtk::Widget>>(self, widget: &W) -> i32 {
+        match self {
+            Self::Pixels(size) => size,
+            Self::Points(size) => points_to_pixels(size, widget).round() as i32,
+            Self::Em(size) => {
+                let font_size = widget
+                    .pango_context()
+                    .font_description()
+                    .filter(|font| font.size() > 0)
+                    .map_or_else(
+                        || points_to_pixels(DEFAULT_FONT_SIZE_PT, widget),
+                        |font| {
+                            let size = f64::from(font.size()) / f64::from(pango::SCALE);
+                            if font.is_size_absolute() {
+                                size
+                            } else {
+                                points_to_pixels(size, widget)
+                            }
+                        },
+                    );
+
+                (size * font_size).round() as i32
+            }
+        }
+    }
+}
+
+fn points_to_pixels<W: IsA<gtk::Widget>>(points: f64, widget: &W) -> f64 {
+    let dpi = widget
+        .screen()
+        .map(|screen| screen.resolution())
+        .filter(|&dpi| dpi > 0.0)
+        .unwrap_or(DEFAULT_DPI);
+
+    points * dpi / 72.0
+}
+
+impl FromStr for IconSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        let (value, unit) = s
+            .find(|c: char| c.is_ascii_alphabetic())
+            .map_or((s, ""), |i| s.split_at(i));
+
+        let value = value
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite() && *value >= 0.0)
+            .ok_or_else(|| format!("invalid icon size: '{s}'"))?;
+
+        match unit {
+            "" | "px" => Ok(Self::Pixels(value.round() as i32)),
+            "pt" => Ok(Self::Points(value)),
+            "em" => Ok(Self::Em(value)),
+            _ => Err(format!(
+                "invalid icon size unit '{unit}', expected one of 'px', 'pt', 'em'"
+            )),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for IconSize {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Pixels(i32),
+            String(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Pixels(size) => Ok(Self::Pixels(size)),
+            Raw::String(size) => size.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for IconSize {
+    fn schema_name() -> String {
+        String::from("IconSize")
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        use schemars::schema::{SchemaObject, SubschemaValidation};
+
+        SchemaObject {
+            subschemas: Some(Box::new(SubschemaValidation {
+                any_of: Some(vec![<i32>::json_schema(gen), <String>::json_schema(gen)]),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::value::Error as ValueError;
+    use serde::de::IntoDeserializer;
+
+    #[test]
+    fn test_parse_units() {
+        assert_eq!("24".parse(), Ok(IconSize::Pixels(24)));
+        assert_eq!("24px".parse(), Ok(IconSize::Pixels(24)));
+        assert_eq!("12pt".parse(), Ok(IconSize::Points(12.0)));
+        assert_eq!("2em".parse(), Ok(IconSize::Em(2.0)));
+    }
+
+    #[test]
+    fn test_parse_fractional() {
+        assert_eq!("1.5em".parse(), Ok(IconSize::Em(1.5)));
+        assert_eq!(" 0.75 em ".parse(), Ok(IconSize::Em(0.75)));
+        assert_eq!("23.6px".parse(), Ok(IconSize::Pixels(24)));
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid() {
+        for input in ["", "px", "-4px", "-1", "12rem", "big", "NaNpx"] {
+            assert!(
+                input.parse::<IconSize>().is_err(),
+                "expected '{input}' to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let size = IconSize::deserialize(32.into_deserializer());
+        assert_eq!(size, Ok::<_, ValueError>(IconSize::Pixels(32)));
+
+        let size = IconSize::deserialize("1.5em".into_deserializer());
+        assert_eq!(size, Ok::<_, ValueError>(IconSize::Em(1.5)));
+
+        let size: Result<IconSize, ValueError> = IconSize::deserialize("12rem".into_deserializer());
+        assert!(size.is_err());
+    }
+}
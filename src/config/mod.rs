@@ -1,4 +1,5 @@
 mod common;
+mod icon_size;
 mod r#impl;
 mod truncate;
 
@@ -11,6 +12,7 @@ use crate::modules::clock::ClockModule;
 use crate::modules::custom::CustomModule;
 #[cfg(feature = "focused")]
 use crate::modules::focused::FocusedModule;
+use crate::modules::group::GroupModule;
 use crate::modules::label::LabelModule;
 #[cfg(feature = "launcher")]
 use crate::modules::launcher::LauncherModule;
@@ -40,6 +42,7 @@ use std::collections::HashMap;
 use schemars::JsonSchema;
 
 pub use self::common::{CommonConfig, ModuleOrientation, TransitionType};
+pub use self::icon_size::IconSize;
 pub use self::truncate::TruncateMode;
 
 #[derive(Debug, Deserialize, Clone)]
@@ -55,6 +58,7 @@ pub enum ModuleConfig {
     Custom(Box<CustomModule>),
     #[cfg(feature = "focused")]
     Focused(Box<FocusedModule>),
+    Group(Box<GroupModule>),
     Label(Box<LabelModule>),
     #[cfg(feature = "launcher")]
     Launcher(Box<LauncherModule>),
@@ -76,12 +80,14 @@ pub enum ModuleConfig {
 }
 
 impl ModuleConfig {
+    /// Creates the module and adds it to the container,
+    /// returning the widget which wraps it.
     pub fn create(
         self,
         module_factory: &AnyModuleFactory,
         container: &gtk::Box,
         info: &ModuleInfo,
-    ) -> Result<()> {
+    ) -> Result<gtk::EventBox> {
         macro_rules! create {
             ($module:expr) => {
                 module_factory.create(*$module, container, info)
@@ -98,6 +104,7 @@ impl ModuleConfig {
             Self::Custom(module) => create!(module),
             #[cfg(feature = "focused")]
             Self::Focused(module) => create!(module),
+            Self::Group(module) => create!(module),
             Self::Label(module) => create!(module),
             #[cfg(feature = "launcher")]
             Self::Launcher(module) => create!(module),
@@ -118,6 +125,49 @@ impl ModuleConfig {
             Self::Workspaces(module) => create!(module),
         }
     }
+
+    /// Gets the module's configured `name`, if it has one.
+    pub fn name(&self) -> Option<&str> {
+        macro_rules! name {
+            ($module:expr) => {
+                $module
+                    .common
+                    .as_ref()
+                    .and_then(|common| common.name.as_deref())
+            };
+        }
+
+        match self {
+            #[cfg(feature = "cairo")]
+            Self::Cairo(module) => name!(module),
+            #[cfg(feature = "clipboard")]
+            Self::Clipboard(module) => name!(module),
+            #[cfg(feature = "clock")]
+            Self::Clock(module) => name!(module),
+            Self::Custom(module) => name!(module),
+            #[cfg(feature = "focused")]
+            Self::Focused(module) => name!(module),
+            Self::Group(module) => name!(module),
+            Self::Label(module) => name!(module),
+            #[cfg(feature = "launcher")]
+            Self::Launcher(module) => name!(module),
+            #[cfg(feature = "music")]
+            Self::Music(module) => name!(module),
+            #[cfg(feature = "notifications")]
+            Self::Notifications(module) => name!(module),
+            Self::Script(module) => name!(module),
+            #[cfg(feature = "sys_info")]
+            Self::SysInfo(module) => name!(module),
+            #[cfg(feature = "tray")]
+            Self::Tray(module) => name!(module),
+            #[cfg(feature = "upower")]
+            Self::Upower(module) => name!(module),
+            #[cfg(feature = "volume")]
+            Self::Volume(module) => name!(module),
+            #[cfg(feature = "workspaces")]
+            Self::Workspaces(module) => name!(module),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -156,6 +206,54 @@ pub struct MarginConfig {
     pub top: i32,
 }
 
+/// Controls how compositor clients reconnect after losing their connection.
+///
+/// The delay between attempts doubles after each failure,
+/// up to `max_delay`.
+#[cfg(feature = "workspaces")]
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ReconnectConfig {
+    /// The number of failed attempts before giving up.
+    /// Set to `null` to retry forever.
+    ///
+    /// **Default**: `null`
+    #[serde(default)]
+    pub max_retries: Option<u32>,
+
+    /// The delay before the first attempt, in milliseconds.
+    ///
+    /// **Default**: `500`
+    #[serde(default = "default_reconnect_base_delay")]
+    pub base_delay: u64,
+
+    /// The longest delay between attempts, in milliseconds.
+    ///
+    /// **Default**: `30000`
+    #[serde(default = "default_reconnect_max_delay")]
+    pub max_delay: u64,
+
+    /// Overrides for individual clients,
+    /// keyed by client name (`sway`, `hyprland`, `niri` or `wayfire`).
+    ///
+    /// Options not set in an override use their defaults,
+    /// rather than the values above.
+    ///
+    /// **Default**: `{}`
+    #[serde(default)]
+    pub clients: HashMap<String, ReconnectConfig>,
+}
+
+#[cfg(feature = "workspaces")]
+const fn default_reconnect_base_delay() -> u64 {
+    500
+}
+
+#[cfg(feature = "workspaces")]
+const fn default_reconnect_max_delay() -> u64 {
+    30_000
+}
+
 /// The following is a list of all top-level bar config options.
 ///
 /// These options can either be written at the very top object of your config,
@@ -356,6 +454,20 @@ pub struct Config {
     /// ```
     pub ironvar_defaults: Option<HashMap<Box<str>, String>>,
 
+    /// The maximum number of decoded images to keep in memory,
+    /// so that icons which are loaded repeatedly do not need to be re-read from disk.
+    /// Set to `0` to disable the cache.
+    ///
+    /// **Default**: `64`
+    pub image_cache_size: Option<usize>,
+
+    /// How compositor clients reconnect after losing their connection,
+    /// for example when the compositor restarts.
+    ///
+    /// **Default**: retry forever, starting at `500`ms and backing off up to `30000`ms.
+    #[cfg(feature = "workspaces")]
+    pub reconnect: Option<ReconnectConfig>,
+
     /// The configuration for the bar.
     /// Setting through this will enable a single identical bar on each monitor.
     #[serde(flatten)]
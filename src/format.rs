@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+
+/// A value which can be substituted into a format template.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Text(String),
+    Number(f64),
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Self::Text(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Self::Number(value as f64)
+    }
+}
+
+impl From<u8> for Value {
+    fn from(value: u8) -> Self {
+        Self::Number(f64::from(value))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+/// Formatting options for a single token,
+/// parsed from the part of the token after the `:`.
+#[derive(Debug, Default, Clone, Copy)]
+struct Spec {
+    align: Option<Align>,
+    width: usize,
+    precision: Option<usize>,
+}
+
+impl Spec {
+    /// Parses a spec in the form `[align][width][.precision]`.
+    /// Returns `None` if the spec is malformed.
+    fn parse(spec: &str) -> Option<Self> {
+        let mut chars = spec.chars().peekable();
+
+        let align = match chars.peek() {
+            Some('<') => Some(Align::Left),
+            Some('>') => Some(Align::Right),
+            Some('^') => Some(Align::Center),
+            _ => None,
+        };
+
+        if align.is_some() {
+            chars.next();
+        }
+
+        let rest = chars.collect::<String>();
+        let (width, precision) = rest
+            .split_once('.')
+            .map_or((rest.as_str(), None), |(w, p)| (w, Some(p)));
+
+        let width = if width.is_empty() {
+            0
+        } else {
+            width.parse().ok()?
+        };
+
+        let precision = match precision {
+            Some(precision) => Some(precision.parse().ok()?),
+            None => None,
+        };
+
+        Some(Self {
+            align,
+            width,
+            precision,
+        })
+    }
+
+    fn apply(self, value: &Value) -> String {
+        let (string, default_align) = match value {
+            Value::Text(text) => (text.clone(), Align::Left),
+            Value::Number(number) => {
+                let string = match self.precision {
+                    Some(precision) => format!("{number:.precision$}"),
+                    None => number.to_string(),
+                };
+
+                (string, Align::Right)
+            }
+        };
+
+        let width = self.width;
+        match self.align.unwrap_or(default_align) {
+            Align::Left => format!("{string:<width$}"),
+            Align::Right => format!("{string:>width$}"),
+            Align::Center => format!("{string:^width$}"),
+        }
+    }
+}
+
+/// Renders a format template,
+/// replacing each `{token}` with its value from `values`.
+///
+/// A token may include a spec after a colon,
+/// in the form `{token:[align][width][.precision]}`,
+/// where `align` is one of `<`, `>` or `^`.
+/// Precision only applies to numeric values.
+///
+/// Literal braces are written as `{{` and `}}`.
+/// Tokens which are not present in `values`, or have an invalid spec,
+/// are left in the output untouched.
+///
+/// # Example
+///
+/// ```rs
+/// let values = HashMap::from([("percentage", Value::from(87.5))]);
+/// assert_eq!(render("{percentage:.0}%", &values), "88%");
+/// ```
+pub fn render(template: &str, values: &HashMap<&str, Value>) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut chars = template.char_indices().peekable();
+
+    while let Some((i, char)) = chars.next() {
+        match char {
+            '{' if chars.next_if(|&(_, c)| c == '{').is_some() => output.push('{'),
+            '}' if chars.next_if(|&(_, c)| c == '}').is_some() => output.push('}'),
+            '{' => {
+                let Some(end) = template[i..].find('}').map(|end| i + end) else {
+                    output.push_str(&template[i..]);
+                    break;
+                };
+
+                let token = &template[i + 1..end];
+                let (name, spec) = token
+                    .split_once(':')
+                    .map_or((token, Some(Spec::default())), |(name, spec)| {
+                        (name, Spec::parse(spec))
+                    });
+
+                match (values.get(name), spec) {
+                    (Some(value), Some(spec)) => output.push_str(&spec.apply(value)),
+                    _ => output.push_str(&template[i..=end]),
+                }
+
+                while chars.next_if(|&(j, _)| j <= end).is_some() {}
+            }
+            _ => output.push(char),
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values() -> HashMap<&'static str, Value> {
+        HashMap::from([
+            ("name", Value::from("wlan0")),
+            ("percentage", Value::from(87.5)),
+            ("count", Value::from(3_i64)),
+        ])
+    }
+
+    #[test]
+    fn test_static() {
+        assert_eq!(render("hello world", &values()), "hello world");
+    }
+
+    #[test]
+    fn test_tokens() {
+        assert_eq!(
+            render("{name}: {percentage}% ({count})", &values()),
+            "wlan0: 87.5% (3)"
+        );
+    }
+
+    #[test]
+    fn test_missing_token() {
+        assert_eq!(render("{name} {missing}", &values()), "wlan0 {missing}");
+    }
+
+    #[test]
+    fn test_unclosed_token() {
+        assert_eq!(render("{name} {percentage", &values()), "wlan0 {percentage");
+    }
+
+    #[test]
+    fn test_precision() {
+        assert_eq!(render("{percentage:.0}", &values()), "88");
+        assert_eq!(render("{percentage:.2}", &values()), "87.50");
+        assert_eq!(render("{count:.1}", &values()), "3.0");
+    }
+
+    #[test]
+    fn test_precision_ignored_for_text() {
+        assert_eq!(render("{name:.2}", &values()), "wlan0");
+    }
+
+    #[test]
+    fn test_padding() {
+        assert_eq!(render("[{name:7}]", &values()), "[wlan0  ]");
+        assert_eq!(render("[{count:3}]", &values()), "[  3]");
+        assert_eq!(render("[{name:>7}]", &values()), "[  wlan0]");
+        assert_eq!(render("[{count:<3}]", &values()), "[3  ]");
+        assert_eq!(render("[{name:^7}]", &values()), "[ wlan0 ]");
+        assert_eq!(render("[{percentage:>6.1}]", &values()), "[  87.5]");
+    }
+
+    #[test]
+    fn test_invalid_spec() {
+        assert_eq!(render("{count:x}", &values()), "{count:x}");
+    }
+
+    #[test]
+    fn test_literal_braces() {
+        assert_eq!(render("{{name}}", &values()), "{name}");
+        assert_eq!(render("{{{name}}}", &values()), "{wlan0}");
+        assert_eq!(render("a } b", &values()), "a } b");
+    }
+}
@@ -0,0 +1,178 @@
+use gtk::gdk_pixbuf::Pixbuf;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tracing::debug;
+
+/// The number of images to keep in the cache
+/// when no size is set in the config.
+pub const DEFAULT_CAPACITY: usize = 64;
+
+static CAPACITY: AtomicUsize = AtomicUsize::new(DEFAULT_CAPACITY);
+
+/// The number of lookups between each log of the cache hit rate.
+const STATS_INTERVAL: u64 = 100;
+
+/// Key for cached images:
+/// the resolved image location, scaled size and color, if recolored.
+pub type ImageCacheKey = (String, i32, Option<String>);
+
+thread_local! {
+    /// Decoded images, shared by all image providers.
+    ///
+    /// `Pixbuf`s are only ever loaded on the GTK thread,
+    /// so the cache lives there.
+    static IMAGE_CACHE: RefCell<LruCache<ImageCacheKey, Pixbuf>> = RefCell::new(LruCache::default());
+}
+
+/// Sets the maximum number of decoded images to keep in memory.
+/// A capacity of `0` disables the cache.
+pub fn set_capacity(capacity: usize) {
+    CAPACITY.store(capacity, Ordering::Relaxed);
+}
+
+/// Gets an image from the cache,
+/// marking it as the most recently used.
+pub fn get(key: &ImageCacheKey) -> Option<Pixbuf> {
+    IMAGE_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let pixbuf = cache.get(key).cloned();
+
+        if (cache.hits + cache.misses) % STATS_INTERVAL == 0 {
+            debug!(
+                "Image cache: {} hits, {} misses, {} evictions",
+                cache.hits, cache.misses, cache.evictions
+            );
+        }
+
+        pixbuf
+    })
+}
+
+/// Adds an image to the cache,
+/// evicting the least recently used images if over capacity.
+pub fn insert(key: ImageCacheKey, pixbuf: Pixbuf) {
+    let capacity = CAPACITY.load(Ordering::Relaxed);
+
+    IMAGE_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let evicted = cache.insert(key, pixbuf, capacity);
+
+        if evicted > 0 {
+            debug!(
+                "Evicted {evicted} images from cache ({} hits, {} misses, {} evictions)",
+                cache.hits, cache.misses, cache.evictions
+            );
+        }
+    });
+}
+
+/// Least-recently-used cache.
+///
+/// Eviction scans every entry,
+/// which is fine for the small number of images kept.
+#[derive(Debug)]
+struct LruCache<K, V> {
+    entries: HashMap<K, (V, u64)>,
+    /// Incremented on each access,
+    /// giving the order entries were last used.
+    tick: u64,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl<K, V> Default for LruCache<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            tick: 0,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        }
+    }
+}
+
+impl<K: Eq + Hash + Clone, V> LruCache<K, V> {
+    fn get(&mut self, key: &K) -> Option<&V> {
+        self.tick += 1;
+
+        if let Some((value, last_used)) = self.entries.get_mut(key) {
+            *last_used = self.tick;
+            self.hits += 1;
+            Some(value)
+        } else {
+            self.misses += 1;
+            None
+        }
+    }
+
+    /// Inserts an entry,
+    /// returning the number of entries evicted to make room for it.
+    fn insert(&mut self, key: K, value: V, capacity: usize) -> usize {
+        self.tick += 1;
+        self.entries.insert(key, (value, self.tick));
+
+        let mut evicted = 0;
+
+        while self.entries.len() > capacity {
+            let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+
+            self.entries.remove(&oldest);
+            evicted += 1;
+        }
+
+        self.evictions += evicted as u64;
+        evicted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let mut cache = LruCache::default();
+
+        cache.insert("a", 1, 2);
+        cache.insert("b", 2, 2);
+
+        // use `a` so that `b` is the oldest
+        assert_eq!(cache.get(&"a"), Some(&1));
+
+        assert_eq!(cache.insert("c", 3, 2), 1);
+        assert_eq!(cache.evictions, 1);
+
+        assert_eq!(cache.get(&"a"), Some(&1));
+        assert_eq!(cache.get(&"b"), None);
+        assert_eq!(cache.get(&"c"), Some(&3));
+    }
+
+    #[test]
+    fn test_zero_capacity() {
+        let mut cache = LruCache::default();
+        cache.insert("a", 1, 0);
+        assert_eq!(cache.get(&"a"), None);
+    }
+
+    #[test]
+    fn test_hit_metrics() {
+        let mut cache = LruCache::default();
+        cache.insert("a", 1, 1);
+
+        cache.get(&"a");
+        cache.get(&"b");
+
+        assert_eq!((cache.hits, cache.misses), (1, 1));
+    }
+}
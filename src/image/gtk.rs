@@ -3,7 +3,7 @@ use crate::gtk_helpers::IronbarGtkExt;
 use gtk::prelude::*;
 use gtk::{Button, IconTheme, Image, Label, Orientation};
 
-#[cfg(any(feature = "music", feature = "workspaces", feature = "clipboard"))]
+#[cfg(any(feature = "music", feature = "clipboard"))]
 pub fn new_icon_button(input: &str, icon_theme: &IconTheme, size: i32) -> Button {
     let button = Button::new();
 
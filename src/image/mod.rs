@@ -1,7 +1,8 @@
-#[cfg(any(feature = "music", feature = "workspaces", feature = "clipboard"))]
+pub mod cache;
+#[cfg(any(feature = "music", feature = "clipboard"))]
 mod gtk;
 mod provider;
 
-#[cfg(any(feature = "music", feature = "workspaces"))]
+#[cfg(any(feature = "music", feature = "clipboard"))]
 pub use self::gtk::*;
 pub use provider::ImageProvider;
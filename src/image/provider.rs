@@ -1,24 +1,27 @@
+use super::cache::{self, ImageCacheKey};
 use crate::desktop_file::get_desktop_icon_name;
+use crate::gtk_helpers::IronbarGtkExt;
 #[cfg(feature = "http")]
-use crate::{glib_recv_mpsc, send_async, spawn};
-use cfg_if::cfg_if;
+use crate::{glib_recv_mpsc, send_async, spawn, Ironbar};
 use color_eyre::{Help, Report, Result};
+use glib::SourceId;
 use gtk::cairo::Surface;
 use gtk::gdk::ffi::gdk_cairo_surface_create_from_pixbuf;
-use gtk::gdk_pixbuf::Pixbuf;
+use gtk::gdk::RGBA;
+use gtk::gdk_pixbuf::{Colorspace, InterpType, Pixbuf, PixbufAnimation, PixbufAnimationIter};
+use gtk::gio::{Cancellable, MemoryInputStream};
 use gtk::prelude::*;
 use gtk::{IconLookupFlags, IconTheme};
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::{Debug, Formatter};
+use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::SystemTime;
 #[cfg(feature = "http")]
 use tokio::sync::mpsc;
-use tracing::{debug, warn};
-
-cfg_if!(
-    if #[cfg(feature = "http")] {
-        use gtk::gio::{Cancellable, MemoryInputStream};
-        use tracing::error;
-    }
-);
+use tracing::{debug, error, warn};
 
 #[derive(Debug)]
 enum ImageLocation<'a> {
@@ -28,6 +31,7 @@ enum ImageLocation<'a> {
     },
     Local(PathBuf),
     Steam(String),
+    Data(DataImage),
     #[cfg(feature = "http")]
     Remote(reqwest::Url),
 }
@@ -35,8 +39,98 @@ enum ImageLocation<'a> {
 pub struct ImageProvider<'a> {
     location: ImageLocation<'a>,
     size: i32,
+    color: Option<RGBA>,
+    animate: bool,
+    /// Theme to load the fallback icon from,
+    /// if the fallback should be used.
+    #[cfg_attr(not(feature = "http"), allow(dead_code))]
+    fallback_theme: Option<&'a IconTheme>,
+}
+
+/// An image embedded in a base64 `data:` URI.
+struct DataImage {
+    mime: String,
+    bytes: glib::Bytes,
+}
+
+impl Debug for DataImage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // the payload is not useful in logs
+        f.debug_struct("DataImage")
+            .field("mime", &self.mime)
+            .field("len", &self.bytes.len())
+            .finish()
+    }
+}
+
+impl DataImage {
+    /// Parses the part of a data URI after the `data:` scheme,
+    /// which must be a base64 encoded image in a format GDK can load.
+    fn parse(input: &str) -> Result<Self> {
+        let (header, payload) = input
+            .split_once(',')
+            .ok_or_else(|| Report::msg("Data URI is missing its payload"))?;
+
+        let mime = header
+            .strip_suffix(";base64")
+            .ok_or_else(|| Report::msg("Only base64 encoded data URIs are supported"))?;
+
+        if !mime.starts_with("image/") {
+            return Err(Report::msg(format!(
+                "Unsupported data URI type '{mime}', expected an image"
+            )));
+        }
+
+        let is_supported = Pixbuf::formats().iter().any(|format| {
+            format
+                .mime_types()
+                .iter()
+                .any(|supported| supported.as_str() == mime)
+        });
+
+        if !is_supported {
+            return Err(Report::msg(format!("Unsupported image type '{mime}'")));
+        }
+
+        let payload = payload
+            .chars()
+            .filter(|c| !c.is_ascii_whitespace())
+            .collect::<String>();
+
+        // `g_base64_decode` does not validate its input
+        let data = payload.trim_end_matches('=');
+        let is_valid = !data.is_empty()
+            && payload.len() % 4 == 0
+            && payload.len() - data.len() <= 2
+            && data
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '/');
+
+        if !is_valid {
+            return Err(Report::msg("Data URI payload is not valid base64"));
+        }
+
+        Ok(Self {
+            mime: mime.to_string(),
+            bytes: glib::Bytes::from_owned(glib::base64_decode(&payload)),
+        })
+    }
 }
 
+/// The largest remote image which will be downloaded, in bytes.
+#[cfg(feature = "http")]
+const MAX_DOWNLOAD_SIZE: usize = 10 * 1024 * 1024;
+
+/// The most downloaded images to keep in the on-disk cache.
+#[cfg(feature = "http")]
+const HTTP_CACHE_MAX_FILES: usize = 256;
+
+/// Widget tag holding the pending frame timer
+/// of the animation currently playing in an image.
+const ANIMATION_TAG: &str = "image-animation";
+
+type AnimationTimer = Rc<RefCell<Option<SourceId>>>;
+
 impl<'a> ImageProvider<'a> {
     /// Attempts to parse the image input to find its location.
     /// Errors if no valid location type can be found.
@@ -44,10 +138,84 @@ impl<'a> ImageProvider<'a> {
     /// Note this checks that icons exist in theme, or files exist on disk
     /// but no other check is performed.
     pub fn parse(input: &str, theme: &'a IconTheme, use_fallback: bool, size: i32) -> Option<Self> {
-        let location = Self::get_location(input, theme, size, use_fallback, 0)?;
-        debug!("Resolved {input} --> {location:?} (size: {size})");
+        Self::parse_first(&[input], theme, use_fallback, size)
+    }
 
-        Some(Self { location, size })
+    /// Attempts to parse each image input in order,
+    /// returning the first which can be found.
+    /// The candidate which was used is logged.
+    ///
+    /// Icons missing from the theme are skipped,
+    /// apart from the last input, which is parsed in the same manner as [`Self::parse`].
+    /// This allows a custom icon to be tried before a standard one, for example.
+    pub fn parse_first(
+        inputs: &[&str],
+        theme: &'a IconTheme,
+        use_fallback: bool,
+        size: i32,
+    ) -> Option<Self> {
+        let last = inputs.len().checked_sub(1)?;
+
+        let (index, location) = inputs.iter().enumerate().find_map(|(i, input)| {
+            let is_last = i == last;
+            let location = Self::get_location(input, theme, size, use_fallback && is_last, 0)?;
+
+            let is_missing_icon = matches!(
+                &location,
+                ImageLocation::Icon { name, .. }
+                    if theme.lookup_icon(name, size, IconLookupFlags::empty()).is_none()
+            );
+
+            (is_last || !is_missing_icon).then_some((i, location))
+        })?;
+
+        if inputs.len() > 1 {
+            debug!(
+                "Resolved candidate {} of {}: {} --> {location:?} (size: {size})",
+                index + 1,
+                inputs.len(),
+                inputs[index]
+            );
+        } else {
+            debug!("Resolved {} --> {location:?} (size: {size})", inputs[index]);
+        }
+
+        Some(Self {
+            location,
+            size,
+            color: None,
+            animate: true,
+            fallback_theme: use_fallback.then_some(theme),
+        })
+    }
+
+    /// Sets whether animated images (GIF and WEBP files) should play.
+    /// When disabled, only the first frame is shown.
+    ///
+    /// Animations are enabled by default.
+    pub fn animate(mut self, animate: bool) -> Self {
+        self.animate = animate;
+        self
+    }
+
+    /// Attempts to parse the image input to find its location,
+    /// in the same manner as [`Self::parse`].
+    ///
+    /// Symbolic icons and SVG files are recolored to `color` when loaded.
+    /// Pass a widget's CSS foreground color
+    /// to have the image match the surrounding text.
+    /// Other images are loaded as normal.
+    pub fn parse_with_color(
+        input: &str,
+        theme: &'a IconTheme,
+        use_fallback: bool,
+        size: i32,
+        color: RGBA,
+    ) -> Option<Self> {
+        Self::parse(input, theme, use_fallback, size).map(|provider| Self {
+            color: Some(color),
+            ..provider
+        })
     }
 
     /// Returns true if the input starts with a prefix
@@ -55,6 +223,7 @@ impl<'a> ImageProvider<'a> {
     /// (ie the parser would not fallback to checking the input).
     pub fn is_definitely_image_input(input: &str) -> bool {
         input.starts_with("icon:")
+            || input.starts_with("data:")
             || input.starts_with("file://")
             || input.starts_with("http://")
             || input.starts_with("https://")
@@ -91,6 +260,13 @@ impl<'a> ImageProvider<'a> {
                 name: input_name.to_string(),
                 theme,
             }),
+            Some(input_type) if input_type == "data" => match DataImage::parse(input_name) {
+                Ok(data) => Some(ImageLocation::Data(data)),
+                Err(err) => {
+                    warn!("{err:?}");
+                    fallback!()
+                }
+            },
             Some(input_type) if input_type == "file" => Some(ImageLocation::Local(PathBuf::from(
                 input_name[2..].to_string(),
             ))),
@@ -142,18 +318,61 @@ impl<'a> ImageProvider<'a> {
     /// Attempts to fetch the image from the location
     /// and load it into the provided `GTK::Image` widget.
     pub fn load_into_image(&self, image: gtk::Image) -> Result<()> {
+        // stop any animation previously loaded into the image,
+        // so it does not overwrite this image with its next frame
+        if let Some(timer) = image.get_tag::<AnimationTimer>(ANIMATION_TAG) {
+            stop_animation(timer);
+        }
+
         // handle remote locations async to avoid blocking UI thread while downloading
         #[cfg(feature = "http")]
         if let ImageLocation::Remote(url) = &self.location {
+            let cache_path = Self::get_http_cache_path(url);
+
+            // previously downloaded images are loaded straight from disk
+            if let Some(path) = cache_path.as_ref().filter(|path| path.is_file()) {
+                debug!("Loading {url} from {}", path.display());
+
+                let provider = Self {
+                    location: ImageLocation::Local(path.clone()),
+                    size: self.size,
+                    color: self.color.clone(),
+                    animate: self.animate,
+                    fallback_theme: self.fallback_theme,
+                };
+
+                match provider.load_into_image_sync(&image) {
+                    Ok(()) => return Ok(()),
+                    // the cached file may be corrupt, so remove it and download again
+                    Err(err) => {
+                        warn!("Failed to load cached {url}: {err:?}");
+
+                        let path = path.clone();
+                        spawn(async move {
+                            if let Err(err) = tokio::fs::remove_file(&path).await {
+                                warn!("Failed to remove {}: {err:?}", path.display());
+                            }
+                        });
+                    }
+                }
+            }
+
+            // show the fallback until the download completes,
+            // which also leaves it in place if the download fails
+            self.load_fallback(&image);
+
             let url = url.clone();
             let (tx, rx) = mpsc::channel(64);
 
-            spawn(async move {
-                let bytes = Self::get_bytes_from_http(url).await;
-                if let Ok(bytes) = bytes {
-                    send_async!(tx, bytes);
-                }
-            });
+            {
+                let url = url.clone();
+                spawn(async move {
+                    match Self::get_bytes_from_http(&url).await {
+                        Ok(bytes) => send_async!(tx, bytes),
+                        Err(err) => warn!("Failed to download {url}: {err:?}"),
+                    }
+                });
+            }
 
             {
                 let size = self.size;
@@ -176,7 +395,17 @@ impl<'a> ImageProvider<'a> {
                     {
                         Ok(Err(err)) => error!("{err:?}"),
                         Err(err) => error!("{err:?}"),
-                        _ => {}
+                        // only images which decode are cached
+                        Ok(Ok(())) => {
+                            if let Some(path) = cache_path.clone() {
+                                let url = url.clone();
+                                spawn(async move {
+                                    if let Err(err) = Self::write_http_cache(path, bytes).await {
+                                        warn!("Failed to cache {url}: {err:?}");
+                                    }
+                                });
+                            }
+                        }
                     }
                 });
             }
@@ -195,17 +424,75 @@ impl<'a> ImageProvider<'a> {
     fn load_into_image_sync(&self, image: &gtk::Image) -> Result<()> {
         let scale = image.scale_factor();
 
-        let pixbuf = match &self.location {
-            ImageLocation::Icon { name, theme } => self.get_from_icon(name, theme, scale),
-            ImageLocation::Local(path) => self.get_from_file(path, scale),
-            ImageLocation::Steam(steam_id) => self.get_from_steam_id(steam_id, scale),
+        if let (ImageLocation::Local(path), None) = (&self.location, &self.color) {
+            if self.animate && self.load_animation(path, image)? {
+                return Ok(());
+            }
+        }
+
+        let key = self.cache_key(scale);
+
+        if let Some(pixbuf) = key.as_ref().and_then(cache::get) {
+            return Self::create_and_load_surface(&pixbuf, image);
+        }
+
+        let pixbuf = match (&self.location, &self.color) {
+            (_, Some(color)) => self.get_recolored(color, scale),
+            (ImageLocation::Icon { name, theme }, None) => self.get_from_icon(name, theme, scale),
+            (ImageLocation::Local(path), None) => self.get_from_file(path, scale),
+            (ImageLocation::Steam(steam_id), None) => self.get_from_steam_id(steam_id, scale),
+            (ImageLocation::Data(data), None) => self.get_from_data(data, scale),
             #[cfg(feature = "http")]
             _ => unreachable!(), // handled above
         }?;
 
+        if let Some(key) = key {
+            cache::insert(key, pixbuf.clone());
+        }
+
         Self::create_and_load_surface(&pixbuf, image)
     }
 
+    /// Gets the key to cache the loaded image under.
+    ///
+    /// Icons are keyed by their resolved file,
+    /// so the same icon name from different themes is cached separately.
+    /// Local files include their modification time,
+    /// so files which are overwritten are reloaded.
+    fn cache_key(&self, scale: i32) -> Option<ImageCacheKey> {
+        let location = match &self.location {
+            ImageLocation::Icon { name, theme } => {
+                let info = theme.lookup_icon_for_scale(
+                    name,
+                    self.size,
+                    scale,
+                    IconLookupFlags::empty(),
+                )?;
+
+                info.filename()?.display().to_string()
+            }
+            ImageLocation::Local(path) => {
+                let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+                format!("{}@{modified:?}", path.display())
+            }
+            ImageLocation::Steam(steam_id) => format!("steam:{steam_id}"),
+            ImageLocation::Data(data) => {
+                let mut hasher = DefaultHasher::new();
+                data.bytes[..].hash(&mut hasher);
+
+                format!("data:{}:{:016x}", data.mime, hasher.finish())
+            }
+            #[cfg(feature = "http")]
+            ImageLocation::Remote(_) => return None,
+        };
+
+        Some((
+            location,
+            self.size * scale,
+            self.color.as_ref().map(ToString::to_string),
+        ))
+    }
+
     /// Attempts to create a Cairo surface from the provided `Pixbuf`,
     /// using the provided scaling factor.
     /// The surface is then loaded into the provided image.
@@ -226,6 +513,102 @@ impl<'a> ImageProvider<'a> {
         Ok(())
     }
 
+    /// Attempts to load an animated image from a local file,
+    /// and starts playing it in the image.
+    ///
+    /// Returns `false` without loading anything
+    /// if the file is not an animated format, or only has a single frame.
+    fn load_animation(&self, path: &Path, image: &gtk::Image) -> Result<bool> {
+        let is_animated_format = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("gif") || ext.eq_ignore_ascii_case("webp"));
+
+        if !is_animated_format {
+            return Ok(false);
+        }
+
+        let animation = PixbufAnimation::from_file(path)?;
+        if animation.is_static_image() {
+            return Ok(false);
+        }
+
+        let iter = animation.iter(Some(SystemTime::now()));
+        let size = self.size * image.scale_factor();
+
+        load_animation_frame(&iter, image, size)?;
+
+        // the timer is shared by every animation loaded into the image,
+        // so that only a single destroy handler is ever connected
+        let timer = match image.get_tag::<AnimationTimer>(ANIMATION_TAG) {
+            Some(timer) => timer.clone(),
+            None => {
+                let timer = AnimationTimer::default();
+
+                // the pending timer holds a reference to the frame iterator,
+                // so it must be removed along with the image
+                image.connect_destroy({
+                    let timer = timer.clone();
+                    move |_| stop_animation(&timer)
+                });
+
+                image.set_tag(ANIMATION_TAG, timer.clone());
+                timer
+            }
+        };
+
+        schedule_animation_frame(image, iter, size, timer);
+
+        Ok(true)
+    }
+
+    /// Attempts to get a `Pixbuf` recolored to the provided color.
+    fn get_recolored(&self, color: &RGBA, scale: i32) -> Result<Pixbuf> {
+        match &self.location {
+            ImageLocation::Icon { name, theme } => {
+                self.get_from_icon_symbolic(name, theme, color, scale)
+            }
+            ImageLocation::Local(path) => {
+                let pixbuf = self.get_from_file(path, scale)?;
+
+                if path.extension().is_some_and(|ext| ext == "svg") {
+                    recolor(&pixbuf, color)
+                } else {
+                    Ok(pixbuf)
+                }
+            }
+            ImageLocation::Steam(steam_id) => self.get_from_steam_id(steam_id, scale),
+            ImageLocation::Data(data) => {
+                let pixbuf = self.get_from_data(data, scale)?;
+
+                if data.mime == "image/svg+xml" {
+                    recolor(&pixbuf, color)
+                } else {
+                    Ok(pixbuf)
+                }
+            }
+            #[cfg(feature = "http")]
+            ImageLocation::Remote(_) => unreachable!(), // handled in `load_into_image`
+        }
+    }
+
+    /// Attempts to get a `Pixbuf` from the GTK icon theme,
+    /// rendering symbolic icons in the provided color.
+    /// Non-symbolic icons are loaded as normal.
+    fn get_from_icon_symbolic(
+        &self,
+        name: &str,
+        theme: &IconTheme,
+        color: &RGBA,
+        scale: i32,
+    ) -> Result<Pixbuf> {
+        let info = theme
+            .lookup_icon_for_scale(name, self.size, scale, IconLookupFlags::FORCE_SIZE)
+            .ok_or_else(|| Report::msg(format!("Icon theme does not contain icon '{name}'")))?;
+
+        let (pixbuf, _) = info.load_symbolic(color, None, None, None)?;
+        Ok(pixbuf)
+    }
+
     /// Attempts to get a `Pixbuf` from the GTK icon theme.
     fn get_from_icon(&self, name: &str, theme: &IconTheme, scale: i32) -> Result<Pixbuf> {
         let pixbuf =
@@ -247,6 +630,22 @@ impl<'a> ImageProvider<'a> {
         Ok(pixbuf)
     }
 
+    /// Attempts to get a `Pixbuf` from the decoded contents of a data URI.
+    fn get_from_data(&self, data: &DataImage, scale: i32) -> Result<Pixbuf> {
+        let scaled_size = self.size * scale;
+        let stream = MemoryInputStream::from_bytes(&data.bytes);
+
+        let pixbuf = Pixbuf::from_stream_at_scale(
+            &stream,
+            scaled_size,
+            scaled_size,
+            true,
+            Some(&Cancellable::new()),
+        )?;
+
+        Ok(pixbuf)
+    }
+
     /// Attempts to get a `Pixbuf` from a local file,
     /// using the Steam game ID to look it up.
     fn get_from_steam_id(&self, steam_id: &str, scale: i32) -> Result<Pixbuf> {
@@ -264,18 +663,124 @@ impl<'a> ImageProvider<'a> {
     }
 
     /// Attempts to get `Bytes` from an HTTP resource asynchronously.
+    /// Resources larger than `MAX_DOWNLOAD_SIZE` are rejected.
     #[cfg(feature = "http")]
-    async fn get_bytes_from_http(url: reqwest::Url) -> Result<glib::Bytes> {
-        let res = reqwest::get(url).await?;
+    async fn get_bytes_from_http(url: &reqwest::Url) -> Result<glib::Bytes> {
+        let mut res = reqwest::get(url.clone()).await?;
 
         let status = res.status();
-        if status.is_success() {
-            let bytes = res.bytes().await?;
-            Ok(glib::Bytes::from_owned(bytes))
-        } else {
-            Err(Report::msg(format!(
+        if !status.is_success() {
+            return Err(Report::msg(format!(
                 "Received non-success HTTP code ({status})"
-            )))
+            )));
+        }
+
+        let too_large = || {
+            Report::msg(format!(
+                "Image is larger than the maximum of {MAX_DOWNLOAD_SIZE} bytes"
+            ))
+        };
+
+        if res
+            .content_length()
+            .is_some_and(|len| len > MAX_DOWNLOAD_SIZE as u64)
+        {
+            return Err(too_large());
+        }
+
+        // the content length is not always sent, so also check as the body arrives
+        let mut bytes = vec![];
+        while let Some(chunk) = res.chunk().await? {
+            if bytes.len() + chunk.len() > MAX_DOWNLOAD_SIZE {
+                return Err(too_large());
+            }
+
+            bytes.extend_from_slice(&chunk);
+        }
+
+        Ok(glib::Bytes::from_owned(bytes))
+    }
+
+    /// Gets the path to cache a remote image at on disk,
+    /// keyed by a hash of its URL.
+    #[cfg(feature = "http")]
+    fn get_http_cache_path(url: &reqwest::Url) -> Option<PathBuf> {
+        let mut hasher = DefaultHasher::new();
+        url.as_str().hash(&mut hasher);
+
+        dirs::cache_dir().map(|dir| {
+            dir.join("ironbar")
+                .join("images")
+                .join(format!("{:016x}", hasher.finish()))
+        })
+    }
+
+    /// Writes a downloaded image to the on-disk cache.
+    ///
+    /// The image is written to a temporary file which then replaces the cached file,
+    /// so that a partially written image is never loaded.
+    #[cfg(feature = "http")]
+    async fn write_http_cache(path: PathBuf, bytes: glib::Bytes) -> Result<()> {
+        let Some(dir) = path.parent() else {
+            return Ok(());
+        };
+
+        tokio::fs::create_dir_all(dir).await?;
+
+        let tmp_path = path.with_extension(format!("tmp-{}", Ironbar::unique_id()));
+        tokio::fs::write(&tmp_path, &*bytes).await?;
+
+        if let Err(err) = tokio::fs::rename(&tmp_path, &path).await {
+            let _ = tokio::fs::remove_file(&tmp_path).await;
+            return Err(err.into());
+        }
+
+        Self::evict_http_cache(dir).await
+    }
+
+    /// Removes the oldest images from the on-disk cache
+    /// once it holds more than `HTTP_CACHE_MAX_FILES`.
+    #[cfg(feature = "http")]
+    async fn evict_http_cache(dir: &Path) -> Result<()> {
+        let mut entries = tokio::fs::read_dir(dir).await?;
+        let mut files = vec![];
+
+        while let Some(entry) = entries.next_entry().await? {
+            let modified = entry.metadata().await?.modified()?;
+            files.push((modified, entry.path()));
+        }
+
+        let Some(excess) = files.len().checked_sub(HTTP_CACHE_MAX_FILES) else {
+            return Ok(());
+        };
+
+        files.sort_unstable();
+
+        for (_, path) in files.into_iter().take(excess) {
+            tokio::fs::remove_file(path).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Loads the fallback icon into the image,
+    /// if the fallback is enabled.
+    #[cfg(feature = "http")]
+    fn load_fallback(&self, image: &gtk::Image) {
+        let Some(theme) = self.fallback_theme else {
+            return;
+        };
+
+        let provider = Self {
+            location: Self::get_fallback_icon(theme),
+            size: self.size,
+            color: None,
+            animate: false,
+            fallback_theme: None,
+        };
+
+        if let Err(err) = provider.load_into_image_sync(image) {
+            error!("{err:?}");
         }
     }
 
@@ -286,3 +791,111 @@ impl<'a> ImageProvider<'a> {
         }
     }
 }
+
+/// Loads the current frame of an animation into the image,
+/// scaled to fit within `size`.
+fn load_animation_frame(iter: &PixbufAnimationIter, image: &gtk::Image, size: i32) -> Result<()> {
+    let frame = iter.pixbuf();
+
+    let (width, height) = (frame.width(), frame.height());
+    let ratio = f64::from(size) / f64::from(width.max(height));
+    let scale = |length: i32| ((f64::from(length) * ratio).round() as i32).max(1);
+
+    let frame = frame
+        .scale_simple(scale(width), scale(height), InterpType::Bilinear)
+        .ok_or_else(|| Report::msg("Failed to scale animation frame"))?;
+
+    ImageProvider::create_and_load_surface(&frame, image)
+}
+
+/// Schedules the next frame of an animation to be loaded into the image,
+/// storing the pending timer in `timer` so it can be stopped.
+///
+/// Each frame schedules the one after it,
+/// until the animation ends or the image is destroyed.
+fn schedule_animation_frame(
+    image: &gtk::Image,
+    iter: PixbufAnimationIter,
+    size: i32,
+    timer: AnimationTimer,
+) {
+    // no delay means the current frame is shown forever
+    let Some(delay) = iter.delay_time() else {
+        return;
+    };
+
+    let image = image.downgrade();
+
+    let source = glib::timeout_add_local_once(delay, {
+        let timer = timer.clone();
+
+        move || {
+            // the source is removed automatically once it has run
+            timer.borrow_mut().take();
+
+            let Some(image) = image.upgrade() else {
+                return;
+            };
+
+            iter.advance(SystemTime::now());
+
+            if let Err(err) = load_animation_frame(&iter, &image, size) {
+                error!("{err:?}");
+                return;
+            }
+
+            schedule_animation_frame(&image, iter, size, timer);
+        }
+    });
+
+    timer.borrow_mut().replace(source);
+}
+
+/// Stops an animation by removing its pending frame timer.
+fn stop_animation(timer: &AnimationTimer) {
+    if let Some(source) = timer.borrow_mut().take() {
+        source.remove();
+    }
+}
+
+/// Creates a copy of the `Pixbuf` with every pixel set to the provided color,
+/// keeping the original alpha channel.
+///
+/// This is intended for single-color images such as symbolic SVGs.
+fn recolor(pixbuf: &Pixbuf, color: &RGBA) -> Result<Pixbuf> {
+    let pixbuf = if pixbuf.has_alpha() {
+        pixbuf.clone()
+    } else {
+        pixbuf.add_alpha(false, 0, 0, 0)?
+    };
+
+    let width = pixbuf.width() as usize;
+    let rowstride = pixbuf.rowstride() as usize;
+    let mut data = pixbuf.read_pixel_bytes().to_vec();
+
+    let to_byte = |channel: f64| (channel.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let (red, green, blue) = (
+        to_byte(color.red()),
+        to_byte(color.green()),
+        to_byte(color.blue()),
+    );
+
+    for row in data.chunks_mut(rowstride) {
+        for pixel in row[..width * 4].chunks_exact_mut(4) {
+            pixel[0] = red;
+            pixel[1] = green;
+            pixel[2] = blue;
+            pixel[3] = (f64::from(pixel[3]) * color.alpha()).round() as u8;
+        }
+    }
+
+    Ok(Pixbuf::from_mut_slice(
+        data,
+        Colorspace::Rgb,
+        true,
+        pixbuf.bits_per_sample(),
+        pixbuf.width(),
+        pixbuf.height(),
+        pixbuf.rowstride(),
+    ))
+}
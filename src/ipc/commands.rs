@@ -23,6 +23,18 @@ pub enum Command {
         path: PathBuf,
     },
 
+    /// Get the state of each client,
+    /// for diagnosing why a module may not be showing anything.
+    Clients,
+
+    /// Destroy a module and create it again from its existing config.
+    ReloadModule {
+        /// The name of the bar.
+        bar_name: String,
+        /// The configured name of the widget.
+        widget_name: String,
+    },
+
     /// Get and set reactive Ironvar values.
     #[command(subcommand)]
     Var(IronvarCommand),
@@ -149,6 +149,28 @@ impl Ipc {
                     Response::error("File not found")
                 }
             }
+            Command::Clients => {
+                let value = ironbar
+                    .clients
+                    .borrow()
+                    .status()
+                    .iter()
+                    .map(|status| format!("{}: {}", status.name, status.state))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+
+                Response::OkValue { value }
+            }
+            Command::ReloadModule {
+                bar_name,
+                widget_name,
+            } => match ironbar.bar_by_name(&bar_name) {
+                Some(bar) => match bar.reload_module(&widget_name) {
+                    Ok(()) => Response::Ok,
+                    Err(err) => Response::error(&err.to_string()),
+                },
+                None => Response::error("Invalid bar name"),
+            },
             Command::Var(cmd) => ironvar::handle_command(cmd),
             Command::Bar(cmd) => bar::handle_command(cmd, ironbar),
         }
@@ -24,7 +24,7 @@ use gtk::Application;
 use smithay_client_toolkit::output::OutputInfo;
 use tokio::runtime::Runtime;
 use tokio::task::{block_in_place, JoinHandle};
-use tracing::{debug, error, info, warn};
+use tracing::{debug, error, info, warn, Instrument, Span};
 use universal_config::ConfigLoader;
 
 use crate::bar::{create_bar, Bar};
@@ -35,6 +35,7 @@ use crate::error::ExitCode;
 #[cfg(feature = "ipc")]
 use crate::ironvar::VariableManager;
 use crate::style::load_css;
+use crate::tasks::TaskGroup;
 
 mod bar;
 #[cfg(feature = "cli")]
@@ -44,6 +45,7 @@ mod config;
 mod desktop_file;
 mod dynamic_value;
 mod error;
+mod format;
 mod gtk_helpers;
 mod image;
 #[cfg(feature = "ipc")]
@@ -56,6 +58,7 @@ mod modules;
 mod popup;
 mod script;
 mod style;
+mod tasks;
 
 pub const APP_ID: &str = "dev.jstanger.ironbar";
 const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -120,9 +123,14 @@ impl Ironbar {
     fn new() -> Self {
         let (config, config_dir) = load_config();
 
+        #[cfg(feature = "workspaces")]
+        let clients = Clients::new(config.reconnect.map(Into::into).unwrap_or_default());
+        #[cfg(not(feature = "workspaces"))]
+        let clients = Clients::new();
+
         Self {
             bars: Rc::new(RefCell::new(vec![])),
-            clients: Rc::new(RefCell::new(Clients::new())),
+            clients: Rc::new(RefCell::new(clients)),
             config: Rc::new(RefCell::new(config)),
             config_dir,
         }
@@ -326,6 +334,12 @@ fn load_config() -> (Config, PathBuf) {
 
     debug!("Loaded config file");
 
+    image::cache::set_capacity(
+        config
+            .image_cache_size
+            .unwrap_or(image::cache::DEFAULT_CAPACITY),
+    );
+
     #[cfg(feature = "ipc")]
     if let Some(ironvars) = config.ironvar_defaults.take() {
         let variable_manager = Ironbar::variable_manager();
@@ -443,7 +457,15 @@ where
     F: Future + Send + 'static,
     F::Output: Send + 'static,
 {
-    Ironbar::runtime().spawn(f)
+    // keep the caller's span, so logs from the task are attributed to it
+    match TaskGroup::current() {
+        Some(group) => {
+            let handle = Ironbar::runtime().spawn(group.scope(f).in_current_span());
+            group.add(handle.abort_handle());
+            handle
+        }
+        None => Ironbar::runtime().spawn(f.in_current_span()),
+    }
 }
 
 /// Calls `spawn_blocking` on the Tokio runtime.
@@ -452,7 +474,8 @@ where
     F: FnOnce() -> R + Send + 'static,
     R: Send + 'static,
 {
-    Ironbar::runtime().spawn_blocking(f)
+    let span = Span::current();
+    Ironbar::runtime().spawn_blocking(move || span.in_scope(f))
 }
 
 /// Blocks on a `Future` until it resolves.
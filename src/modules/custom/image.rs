@@ -34,6 +34,13 @@ pub struct ImageWidget {
     /// **Default**: `32`
     #[serde(default = "default_size")]
     size: i32,
+
+    /// Whether animated images (GIF and WEBP files) should play.
+    /// When disabled, only the first frame is shown.
+    ///
+    /// **Default**: `true`
+    #[serde(default = "crate::config::default_true")]
+    animate: bool,
 }
 
 const fn default_size() -> i32 {
@@ -51,8 +58,11 @@ impl CustomWidget for ImageWidget {
             let icon_theme = context.icon_theme.clone();
 
             dynamic_string(&self.src, move |src| {
-                ImageProvider::parse(&src, &icon_theme, false, self.size)
-                    .map(|image| image.load_into_image(gtk_image.clone()));
+                ImageProvider::parse(&src, &icon_theme, false, self.size).map(|image| {
+                    image
+                        .animate(self.animate)
+                        .load_into_image(gtk_image.clone())
+                });
             });
         }
 
@@ -1,5 +1,7 @@
+#[cfg(any(feature = "workspaces+sway", feature = "workspaces+hyprland"))]
+use crate::clients::compositor::WindowClient;
 use crate::clients::wayland::{self, ToplevelEvent};
-use crate::config::{CommonConfig, TruncateMode};
+use crate::config::{CommonConfig, IconSize, TruncateMode};
 use crate::gtk_helpers::IronbarGtkExt;
 use crate::image::ImageProvider;
 use crate::modules::{Module, ModuleInfo, ModuleParts, ModuleUpdateEvent, WidgetContext};
@@ -25,11 +27,11 @@ pub struct FocusedModule {
     #[serde(default = "crate::config::default_true")]
     show_title: bool,
 
-    /// Icon size in pixels.
+    /// Icon size, in pixels or as a unit string (`px`, `pt` or `em`).
     ///
     /// **Default**: `32`
     #[serde(default = "default_icon_size")]
-    icon_size: i32,
+    icon_size: IconSize,
 
     // -- common --
     /// See [truncate options](module-level-options#truncate-mode).
@@ -54,8 +56,28 @@ impl Default for FocusedModule {
     }
 }
 
-const fn default_icon_size() -> i32 {
-    32
+const fn default_icon_size() -> IconSize {
+    IconSize::Pixels(32)
+}
+
+/// Sends the focused window's title and app id
+/// using the compositor's window focus events.
+#[cfg(any(feature = "workspaces+sway", feature = "workspaces+hyprland"))]
+fn spawn_window_client_controller(
+    client: &dyn WindowClient,
+    tx: mpsc::Sender<ModuleUpdateEvent<Option<(String, String)>>>,
+) {
+    let mut rx = client.subscribe_window_focus();
+
+    spawn(async move {
+        while let Ok(window) = rx.recv().await {
+            // nothing is focused, or focus is on a container
+            let window = (!window.title.is_empty() || window.app_id.is_some())
+                .then(|| (window.title, window.app_id.unwrap_or_default()));
+
+            send_async!(tx, ModuleUpdateEvent::Update(window));
+        }
+    });
 }
 
 impl Module<gtk::Box> for FocusedModule {
@@ -71,6 +93,17 @@ impl Module<gtk::Box> for FocusedModule {
         _rx: mpsc::Receiver<Self::ReceiveMessage>,
     ) -> Result<()> {
         let tx = context.tx.clone();
+
+        // prefer the compositor's own focus events where available
+        #[cfg(any(feature = "workspaces+sway", feature = "workspaces+hyprland"))]
+        match context.try_client::<dyn WindowClient>() {
+            Ok(client) => {
+                spawn_window_client_controller(client.as_ref(), tx);
+                return Ok(());
+            }
+            Err(err) => debug!("Falling back to foreign toplevel for focus: {err}"),
+        }
+
         let wl = context.client::<wayland::Client>();
 
         spawn(async move {
@@ -150,12 +183,14 @@ impl Module<gtk::Box> for FocusedModule {
 
         container.add(&label);
 
+        let icon_size = self.icon_size.resolve(&container);
+
         {
             let icon_theme = icon_theme.clone();
             glib_recv!(context.subscribe(), data => {
                 if let Some((name, id)) = data {
                     if self.show_icon {
-                        match ImageProvider::parse(&id, &icon_theme, true, self.icon_size)
+                        match ImageProvider::parse(&id, &icon_theme, true, icon_size)
                             .map(|image| image.load_into_image(icon.clone()))
                         {
                             Some(Ok(())) => icon.show(),
@@ -0,0 +1,226 @@
+use crate::config::{CommonConfig, IconSize, ModuleConfig, TransitionType};
+use crate::gtk_helpers::IronbarGtkExt;
+use crate::image::ImageProvider;
+use crate::module_impl;
+use crate::modules::{BarModuleFactory, Module, ModuleInfo, ModuleParts, WidgetContext};
+use color_eyre::Result;
+use gtk::gdk::NotifyType;
+use gtk::prelude::*;
+use gtk::{Button, EventBox, Label, Revealer};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tracing::{error, warn};
+
+#[derive(Debug, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GroupModule {
+    /// Image to show on the group button.
+    /// See [here](images) for information on images.
+    ///
+    /// **Default**: `null`
+    icon: Option<String>,
+
+    /// Text to show on the group button.
+    /// Pango markup is supported.
+    ///
+    /// **Default**: `null`
+    label: Option<String>,
+
+    /// The size to render the icon at,
+    /// in pixels or as a unit string (`px`, `pt` or `em`).
+    ///
+    /// **Default**: `24`
+    #[serde(default = "default_icon_size")]
+    icon_size: IconSize,
+
+    /// The action which expands the group to show its modules.
+    ///
+    /// **Valid options**: `hover`, `click`
+    /// <br />
+    /// **Default**: `click`
+    #[serde(default)]
+    expand_on: ExpandOn,
+
+    /// Whether the group starts expanded.
+    ///
+    /// **Default**: `false`
+    #[serde(default)]
+    start_expanded: bool,
+
+    /// The transition animation to use when expanding/collapsing the group.
+    ///
+    /// **Valid options**: `slide_start`, `slide_end`, `crossfade`, `none`
+    /// <br />
+    /// **Default**: `slide_end`
+    #[serde(default = "default_transition_type")]
+    expand_transition_type: TransitionType,
+
+    /// The length in milliseconds
+    /// of the transition animation to use when expanding/collapsing the group.
+    ///
+    /// **Default**: `250`
+    #[serde(default = "default_transition_duration")]
+    expand_transition_duration: u32,
+
+    /// Modules to add to the group.
+    /// Groups cannot be nested inside other groups.
+    ///
+    /// **Default**: `[]`
+    #[serde(default)]
+    modules: Vec<ModuleConfig>,
+
+    /// See [common options](module-level-options#common-options).
+    #[serde(flatten)]
+    pub common: Option<CommonConfig>,
+}
+
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ExpandOn {
+    Hover,
+    #[default]
+    Click,
+}
+
+const fn default_icon_size() -> IconSize {
+    IconSize::Pixels(24)
+}
+
+const fn default_transition_type() -> TransitionType {
+    TransitionType::SlideEnd
+}
+
+const fn default_transition_duration() -> u32 {
+    250
+}
+
+impl Module<EventBox> for GroupModule {
+    type SendMessage = ();
+    type ReceiveMessage = ();
+
+    module_impl!("group");
+
+    fn spawn_controller(
+        &self,
+        _info: &ModuleInfo,
+        _context: &WidgetContext<Self::SendMessage, Self::ReceiveMessage>,
+        _rx: mpsc::Receiver<Self::ReceiveMessage>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn into_widget(
+        self,
+        context: WidgetContext<Self::SendMessage, Self::ReceiveMessage>,
+        info: &ModuleInfo,
+    ) -> Result<ModuleParts<EventBox>> {
+        let orientation = info.bar_position.orientation();
+
+        let event_box = EventBox::new();
+        let container = gtk::Box::new(orientation, 0);
+        event_box.add(&container);
+
+        let button = Button::new();
+        button.add_class("button");
+
+        let button_contents = gtk::Box::new(orientation, 5);
+        button_contents.add_class("contents");
+        button.add(&button_contents);
+
+        if let Some(ref icon) = self.icon {
+            let image = gtk::Image::new();
+            image.add_class("icon");
+            button_contents.add(&image);
+
+            let size = self.icon_size.resolve(&button);
+            if let Some(Err(err)) = ImageProvider::parse(icon, info.icon_theme, false, size)
+                .map(|provider| provider.load_into_image(image))
+            {
+                error!("{err:?}");
+            }
+        }
+
+        if let Some(ref label) = self.label {
+            let label = Label::builder().label(label).use_markup(true).build();
+            label.add_class("label");
+            button_contents.add(&label);
+        }
+
+        container.add(&button);
+
+        let revealer = Revealer::builder()
+            .transition_type(
+                self.expand_transition_type
+                    .to_revealer_transition_type(orientation),
+            )
+            .transition_duration(self.expand_transition_duration)
+            .reveal_child(self.start_expanded)
+            .build();
+
+        let modules = gtk::Box::new(orientation, 0);
+        modules.add_class("modules");
+        revealer.add(&modules);
+        container.add(&revealer);
+
+        let module_factory =
+            BarModuleFactory::new(context.ironbar.clone(), context.popup.clone()).into();
+
+        for module in self.modules {
+            if matches!(module, ModuleConfig::Group(_)) {
+                warn!("Groups cannot be nested inside other groups, skipping");
+                continue;
+            }
+
+            if let Err(err) = module.create(&module_factory, &modules, info) {
+                error!("{err:?}");
+            }
+        }
+
+        let set_expanded = {
+            let event_box = event_box.clone();
+            let revealer = revealer.clone();
+
+            move |expanded: bool| {
+                revealer.set_reveal_child(expanded);
+
+                if expanded {
+                    event_box.add_class("expanded");
+                } else {
+                    event_box.style_context().remove_class("expanded");
+                }
+            }
+        };
+
+        set_expanded(self.start_expanded);
+
+        match self.expand_on {
+            ExpandOn::Click => {
+                button.connect_clicked(move |_| set_expanded(!revealer.reveals_child()));
+            }
+            ExpandOn::Hover => {
+                {
+                    let set_expanded = set_expanded.clone();
+                    event_box.connect_enter_notify_event(move |_, _| {
+                        set_expanded(true);
+                        glib::Propagation::Proceed
+                    });
+                }
+
+                event_box.connect_leave_notify_event(move |_, event| {
+                    // moving onto a child widget also triggers a leave event
+                    if event.detail() != NotifyType::Inferior {
+                        set_expanded(false);
+                    }
+
+                    glib::Propagation::Proceed
+                });
+            }
+        }
+
+        Ok(ModuleParts {
+            widget: event_box,
+            popup: None,
+        })
+    }
+}
@@ -5,7 +5,7 @@ use self::item::{AppearanceOptions, Item, ItemButton, Window};
 use self::open_state::OpenState;
 use super::{Module, ModuleInfo, ModuleParts, ModulePopup, ModuleUpdateEvent, WidgetContext};
 use crate::clients::wayland::{self, ToplevelEvent};
-use crate::config::CommonConfig;
+use crate::config::{CommonConfig, IconSize};
 use crate::desktop_file::find_desktop_file;
 use crate::{arc_mut, glib_recv, lock, module_impl, send_async, spawn, try_send, write_lock};
 use color_eyre::{Help, Report};
@@ -39,11 +39,12 @@ pub struct LauncherModule {
     #[serde(default = "crate::config::default_true")]
     show_icons: bool,
 
-    /// Size in pixels to render icon at (image icons only).
+    /// Size to render icon at (image icons only),
+    /// in pixels or as a unit string (`px`, `pt` or `em`).
     ///
     /// **Default**: `32`
     #[serde(default = "default_icon_size")]
-    icon_size: i32,
+    icon_size: IconSize,
 
     /// Whether items should be added from right-to-left
     /// instead of left-to-right.
@@ -59,8 +60,8 @@ pub struct LauncherModule {
     pub common: Option<CommonConfig>,
 }
 
-const fn default_icon_size() -> i32 {
-    32
+const fn default_icon_size() -> IconSize {
+    IconSize::Pixels(32)
 }
 
 #[derive(Debug, Clone)]
@@ -341,7 +342,7 @@ impl Module<gtk::Box> for LauncherModule {
             let appearance_options = AppearanceOptions {
                 show_names: self.show_names,
                 show_icons: self.show_icons,
-                icon_size: self.icon_size,
+                icon_size: self.icon_size.resolve(&container),
             };
 
             let show_names = self.show_names;
@@ -8,12 +8,13 @@ use gtk::gdk::{EventMask, Monitor};
 use gtk::prelude::*;
 use gtk::{Application, Button, EventBox, IconTheme, Orientation, Revealer, Widget};
 use tokio::sync::{broadcast, mpsc};
-use tracing::debug;
+use tracing::{debug, info_span};
 
 use crate::clients::{ClientResult, ProvidesClient, ProvidesFallibleClient};
 use crate::config::{BarPosition, CommonConfig, TransitionType};
 use crate::gtk_helpers::{IronbarGtkExt, WidgetGeometry};
 use crate::popup::Popup;
+use crate::tasks::TaskGroup;
 use crate::{glib_recv_mpsc, send, Ironbar};
 
 #[cfg(feature = "cairo")]
@@ -31,6 +32,7 @@ pub mod clock;
 pub mod custom;
 #[cfg(feature = "focused")]
 pub mod focused;
+pub mod group;
 pub mod label;
 #[cfg(feature = "launcher")]
 pub mod launcher;
@@ -276,7 +278,7 @@ pub trait ModuleFactory {
         mut module: TModule,
         container: &gtk::Box,
         info: &ModuleInfo,
-    ) -> Result<()>
+    ) -> Result<EventBox>
     where
         TModule: Module<TWidget, SendMessage = TSend, ReceiveMessage = TRev>,
         TWidget: IsA<Widget>,
@@ -301,15 +303,35 @@ pub trait ModuleFactory {
             button_id: usize::MAX, // hack :(
         };
 
-        module.spawn_controller(info, &context, controller_rx)?;
-
         let module_name = TModule::name();
+
+        // tasks spawned by the module join its group,
+        // so they can be stopped when the module is removed
+        let tasks = TaskGroup::default();
+
+        // tasks spawned by the controller inherit the span,
+        // so their logs can be traced back to this instance
+        let res = info_span!("module", name = module_name, id)
+            .in_scope(|| tasks.enter(|| module.spawn_controller(info, &context, controller_rx)));
+
+        // without a widget nothing would stop the tasks started so far
+        if let Err(err) = res {
+            tasks.abort();
+            return Err(err);
+        }
+
         let instance_name = common
             .name
             .clone()
             .unwrap_or_else(|| module_name.to_string());
 
-        let module_parts = module.into_widget(context, info)?;
+        let module_parts = match tasks.enter(|| module.into_widget(context, info)) {
+            Ok(module_parts) => module_parts,
+            Err(err) => {
+                tasks.abort();
+                return Err(err);
+            }
+        };
         module_parts.widget.add_class("widget");
         module_parts.widget.add_class(module_name);
 
@@ -323,7 +345,7 @@ pub trait ModuleFactory {
                 .register_content(id, instance_name, popup_content);
         }
 
-        self.setup_receiver(tx, ui_rx, module_name, id, common.disable_popup);
+        tasks.enter(|| self.setup_receiver(tx, ui_rx, module_name, id, common.disable_popup));
 
         module_parts.setup_identifiers(&common);
 
@@ -332,9 +354,17 @@ pub trait ModuleFactory {
             common,
             info.bar_position.orientation(),
         );
+        // stop everything the module started once its widget is gone,
+        // so that destroyed instances do not keep receiving updates
+        let popup = self.popup().clone();
+        ev_container.connect_destroy(move |_| {
+            tasks.abort();
+            popup.unregister_content(id);
+        });
+
         container.add(&ev_container);
 
-        Ok(())
+        Ok(ev_container)
     }
 
     fn setup_receiver<TSend>(
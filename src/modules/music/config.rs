@@ -1,4 +1,4 @@
-use crate::config::{CommonConfig, TruncateMode};
+use crate::config::{CommonConfig, IconSize, TruncateMode};
 use dirs::{audio_dir, home_dir};
 use serde::Deserialize;
 use std::path::PathBuf;
@@ -112,11 +112,12 @@ pub struct MusicModule {
     #[serde(default = "crate::config::default_true")]
     pub(crate) show_status_icon: bool,
 
-    /// Size to render the icons at, in pixels (image icons only).
+    /// Size to render the icons at (image icons only),
+    /// in pixels or as a unit string (`px`, `pt` or `em`).
     ///
     /// **Default** `32`
     #[serde(default = "default_icon_size")]
-    pub(crate) icon_size: i32,
+    pub(crate) icon_size: IconSize,
 
     /// Size to render the album art image at inside the popup, in pixels.
     ///
@@ -196,8 +197,8 @@ fn default_music_dir() -> PathBuf {
     audio_dir().unwrap_or_else(|| home_dir().map(|dir| dir.join("Music")).unwrap_or_default())
 }
 
-const fn default_icon_size() -> i32 {
-    24
+const fn default_icon_size() -> IconSize {
+    IconSize::Pixels(24)
 }
 
 const fn default_cover_image_size() -> i32 {
@@ -187,8 +187,9 @@ impl Module<Button> for MusicModule {
 
         button.add(&button_contents);
 
-        let icon_play = new_icon_label(&self.icons.play, info.icon_theme, self.icon_size);
-        let icon_pause = new_icon_label(&self.icons.pause, info.icon_theme, self.icon_size);
+        let icon_size = self.icon_size.resolve(&button);
+        let icon_play = new_icon_label(&self.icons.play, info.icon_theme, icon_size);
+        let icon_pause = new_icon_label(&self.icons.pause, info.icon_theme, icon_size);
         let label = Label::new(None);
 
         label.set_use_markup(true);
@@ -272,6 +273,8 @@ impl Module<Button> for MusicModule {
         let container = gtk::Box::new(Orientation::Vertical, 10);
         let main_container = gtk::Box::new(Orientation::Horizontal, 10);
 
+        let icon_size = self.icon_size.resolve(&container);
+
         let album_image = gtk::Image::builder()
             .width_request(128)
             .height_request(128)
@@ -296,16 +299,16 @@ impl Module<Button> for MusicModule {
         let controls_box = gtk::Box::new(Orientation::Horizontal, 0);
         controls_box.add_class("controls");
 
-        let btn_prev = new_icon_button(&icons.prev, icon_theme, self.icon_size);
+        let btn_prev = new_icon_button(&icons.prev, icon_theme, icon_size);
         btn_prev.add_class("btn-prev");
 
-        let btn_play = new_icon_button(&icons.play, icon_theme, self.icon_size);
+        let btn_play = new_icon_button(&icons.play, icon_theme, icon_size);
         btn_play.add_class("btn-play");
 
-        let btn_pause = new_icon_button(&icons.pause, icon_theme, self.icon_size);
+        let btn_pause = new_icon_button(&icons.pause, icon_theme, icon_size);
         btn_pause.add_class("btn-pause");
 
-        let btn_next = new_icon_button(&icons.next, icon_theme, self.icon_size);
+        let btn_next = new_icon_button(&icons.next, icon_theme, icon_size);
         btn_next.add_class("btn-next");
 
         controls_box.add(&btn_prev);
@@ -322,7 +325,7 @@ impl Module<Button> for MusicModule {
         volume_slider.set_inverted(true);
         volume_slider.add_class("slider");
 
-        let volume_icon = new_icon_label(&icons.volume, icon_theme, self.icon_size);
+        let volume_icon = new_icon_label(&icons.volume, icon_theme, icon_size);
         volume_icon.add_class("icon");
 
         volume_box.pack_start(&volume_slider, true, true, 0);
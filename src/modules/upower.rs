@@ -3,12 +3,15 @@ use futures_lite::stream::StreamExt;
 use gtk::{prelude::*, Button};
 use gtk::{Label, Orientation};
 use serde::Deserialize;
+use std::collections::HashMap;
 use tokio::sync::{broadcast, mpsc};
 use upower_dbus::BatteryState;
 use zbus;
 use zbus::fdo::PropertiesProxy;
 
-use crate::config::CommonConfig;
+use crate::clients::dbus::watch_properties;
+use crate::config::{CommonConfig, IconSize};
+use crate::format::{self, Value};
 use crate::gtk_helpers::IronbarGtkExt;
 use crate::image::ImageProvider;
 use crate::modules::PopupButton;
@@ -17,6 +20,15 @@ use crate::modules::{
 };
 use crate::{glib_recv, module_impl, send_async, spawn, try_send};
 
+/// Display device properties shown by the module.
+const PROPERTIES: [&str; 5] = [
+    "Percentage",
+    "IconName",
+    "State",
+    "TimeToFull",
+    "TimeToEmpty",
+];
+
 const DAY: i64 = 24 * 60 * 60;
 const HOUR: i64 = 60 * 60;
 const MINUTE: i64 = 60;
@@ -31,11 +43,12 @@ pub struct UpowerModule {
     #[serde(default = "default_format")]
     format: String,
 
-    /// The size to render the icon at, in pixels.
+    /// The size to render the icon at,
+    /// in pixels or as a unit string (`px`, `pt` or `em`).
     ///
     /// **Default**: `24`
     #[serde(default = "default_icon_size")]
-    icon_size: i32,
+    icon_size: IconSize,
 
     /// See [common options](module-level-options#common-options).
     #[serde(flatten)]
@@ -46,8 +59,8 @@ fn default_format() -> String {
     String::from("{percentage}%")
 }
 
-const fn default_icon_size() -> i32 {
-    24
+const fn default_icon_size() -> IconSize {
+    IconSize::Pixels(24)
 }
 
 #[derive(Clone, Debug)]
@@ -76,12 +89,15 @@ impl Module<gtk::Button> for UpowerModule {
         let display_proxy = context.client::<PropertiesProxy>();
 
         spawn(async move {
-            let mut prop_changed_stream = display_proxy.receive_properties_changed().await?;
-
             let device_interface_name =
                 zbus::names::InterfaceName::from_static_str("org.freedesktop.UPower.Device")
                     .expect("failed to create zbus InterfaceName");
 
+            let mut prop_changed_stream = Box::pin(
+                watch_properties(&display_proxy, device_interface_name.clone(), &PROPERTIES)
+                    .await?,
+            );
+
             let properties = display_proxy.get_all(device_interface_name.clone()).await?;
 
             let percentage = *properties["Percentage"]
@@ -113,17 +129,12 @@ impl Module<gtk::Button> for UpowerModule {
 
             send_async!(tx, ModuleUpdateEvent::Update(properties.clone()));
 
-            while let Some(signal) = prop_changed_stream.next().await {
-                let args = signal.args().expect("Invalid signal arguments");
-                if args.interface_name != device_interface_name {
-                    continue;
-                }
-
-                for (name, changed_value) in args.changed_properties {
-                    match name {
+            while let Some(changed) = prop_changed_stream.next().await {
+                for (name, changed_value) in changed {
+                    match name.as_str() {
                         "Percentage" => {
-                            properties.percentage = changed_value
-                                .downcast::<f64>()
+                            properties.percentage = *changed_value
+                                .downcast_ref::<f64>()
                                 .expect("expected Percentage to be f64");
                         }
                         "IconName" => {
@@ -133,18 +144,19 @@ impl Module<gtk::Button> for UpowerModule {
                                 .to_string();
                         }
                         "State" => {
-                            properties.state =
-                                u32_to_battery_state(changed_value.downcast::<u32>().unwrap_or(0))
-                                    .expect("expected State to be BatteryState");
+                            properties.state = u32_to_battery_state(
+                                changed_value.downcast_ref::<u32>().copied().unwrap_or(0),
+                            )
+                            .expect("expected State to be BatteryState");
                         }
                         "TimeToFull" => {
-                            properties.time_to_full = changed_value
-                                .downcast::<i64>()
+                            properties.time_to_full = *changed_value
+                                .downcast_ref::<i64>()
                                 .expect("expected TimeToFull to be i64");
                         }
                         "TimeToEmpty" => {
-                            properties.time_to_empty = changed_value
-                                .downcast::<i64>()
+                            properties.time_to_empty = *changed_value
+                                .downcast_ref::<i64>()
                                 .expect("expected TimeToEmpty to be i64");
                         }
                         _ => {}
@@ -191,6 +203,7 @@ impl Module<gtk::Button> for UpowerModule {
         });
 
         let format = self.format.clone();
+        let icon_size = self.icon_size.resolve(&button);
 
         let rx = context.subscribe();
         glib_recv!(rx, properties => {
@@ -202,14 +215,20 @@ impl Module<gtk::Button> for UpowerModule {
             else {
                 seconds_to_string(properties.time_to_empty)
             };
-            let format = format.replace("{percentage}", &properties.percentage.to_string())
-                .replace("{time_remaining}", &time_remaining)
-                .replace("{state}", battery_state_to_string(state));
+            let values = HashMap::from([
+                ("percentage", Value::from(properties.percentage)),
+                ("time_remaining", Value::from(time_remaining)),
+                ("state", Value::from(battery_state_to_string(state))),
+            ]);
+            let format = format::render(&format, &values);
 
             let mut icon_name = String::from("icon:");
             icon_name.push_str(&properties.icon_name);
 
-            ImageProvider::parse(&icon_name, &icon_theme, false, self.icon_size)
+            // battery icons are symbolic, so match them to the label text
+            let color = label.style_context().color(label.state_flags());
+
+            ImageProvider::parse_with_color(&icon_name, &icon_theme, false, icon_size, color)
                     .map(|provider| provider.load_into_image(icon.clone()));
 
             label.set_markup(format.as_ref());
@@ -1,5 +1,6 @@
 use crate::clients::volume::{self, Event};
 use crate::config::CommonConfig;
+use crate::format::{self, Value};
 use crate::gtk_helpers::IronbarGtkExt;
 use crate::modules::{
     Module, ModuleInfo, ModuleParts, ModulePopup, ModuleUpdateEvent, PopupButton, WidgetContext,
@@ -222,10 +223,12 @@ impl Module<Button> for VolumeModule {
             glib_recv!(rx, event => {
                 match event {
                     Event::AddSink(sink) | Event::UpdateSink(sink) if sink.active => {
-                        let label = format
-                            .replace("{icon}", if sink.muted { &icons.muted } else { icons.volume_icon(sink.volume) })
-                            .replace("{percentage}", &sink.volume.to_string())
-                            .replace("{name}", &sink.description);
+                        let values = HashMap::from([
+                            ("icon", Value::from(if sink.muted { icons.muted.as_str() } else { icons.volume_icon(sink.volume) })),
+                            ("percentage", Value::from(sink.volume)),
+                            ("name", Value::from(sink.description)),
+                        ]);
+                        let label = format::render(&format, &values);
 
                         button.set_label(&label);
                     },
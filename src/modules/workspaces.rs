@@ -1,12 +1,16 @@
-use crate::clients::compositor::{Visibility, Workspace, WorkspaceClient, WorkspaceUpdate};
-use crate::config::CommonConfig;
+use crate::clients::compositor::{
+    filter_by_output, parse_workspace_num, MoveDirection, SortKey, UnsupportedCompositorError,
+    Visibility, Workspace, WorkspaceClient, WorkspaceLabel, WorkspaceNameMap, WorkspaceUpdate,
+};
+use crate::config::{CommonConfig, IconSize};
 use crate::gtk_helpers::IronbarGtkExt;
-use crate::image::new_icon_button;
+use crate::image::ImageProvider;
 use crate::modules::{Module, ModuleInfo, ModuleParts, ModuleUpdateEvent, WidgetContext};
 use crate::{glib_recv, module_impl, send_async, spawn, try_send, Ironbar};
-use color_eyre::{Report, Result};
+use color_eyre::Result;
+use gtk::gdk::{DragAction, ModifierType};
 use gtk::prelude::*;
-use gtk::{Button, IconTheme};
+use gtk::{Button, DestDefaults, IconTheme, Image, TargetEntry, TargetFlags};
 use serde::Deserialize;
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
@@ -47,13 +51,17 @@ impl Default for Favorites {
 #[derive(Debug, Deserialize, Clone)]
 #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct WorkspacesModule {
-    /// Map of actual workspace names to custom names.
+    /// Map of actual workspace names or numbers to custom names.
     ///
     /// Custom names can be [images](images).
+    /// Prefix a custom name with `text:` to always show it as text.
     ///
     /// If a workspace is not present in the map,
-    /// it will fall back to using its actual name.
-    name_map: Option<HashMap<String, String>>,
+    /// it will fall back to using its number, then its actual name.
+    ///
+    /// **Default**: `{}`
+    #[serde(default)]
+    name_map: WorkspaceNameMap,
 
     /// Workspaces which should always be shown.
     /// This can either be an array of workspace names,
@@ -88,6 +96,13 @@ pub struct WorkspacesModule {
     #[serde(default)]
     hidden: Vec<String>,
 
+    /// Whether to show special (scratchpad) workspaces.
+    /// These are only supported on Hyprland.
+    ///
+    /// **Default**: `true`
+    #[serde(default = "crate::config::default_true")]
+    show_special: bool,
+
     /// Whether to display workspaces from all monitors.
     /// When false, only shows workspaces on the current monitor.
     ///
@@ -96,7 +111,8 @@ pub struct WorkspacesModule {
     all_monitors: bool,
 
     /// The method used for sorting workspaces.
-    /// `added` always appends to the end, `alphanumeric` sorts by number/name.
+    /// `added` always appends to the end, `alphanumeric` sorts by number, then name,
+    /// with special workspaces last.
     ///
     /// **Valid options**: `added`, `alphanumeric`
     /// <br>
@@ -104,34 +120,96 @@ pub struct WorkspacesModule {
     #[serde(default)]
     sort: SortOrder,
 
-    /// The size to render icons at (image icons only).
+    /// Whether workspaces can be reordered
+    /// by dragging one button onto another.
+    /// The dragged workspace moves one position towards the one it is dropped on.
+    /// This is only supported on Sway.
+    ///
+    /// **Default**: `false`
+    #[serde(default = "crate::config::default_false")]
+    drag_reorder: bool,
+
+    /// The size to render icons at (image icons only),
+    /// in pixels or as a unit string (`px`, `pt` or `em`).
     ///
     /// **Default**: `32`
     #[serde(default = "default_icon_size")]
-    icon_size: i32,
+    icon_size: IconSize,
 
     /// See [common options](module-level-options#common-options).
     #[serde(flatten)]
     pub common: Option<CommonConfig>,
 }
 
-const fn default_icon_size() -> i32 {
-    32
+const fn default_icon_size() -> IconSize {
+    IconSize::Pixels(32)
+}
+
+/// An action requested from a workspace button.
+#[derive(Debug, Clone)]
+pub enum WorkspaceAction {
+    /// Focus the workspace with this id.
+    Focus(i64),
+    /// Focus the favourite workspace with this name,
+    /// which did not exist when its button was created.
+    FocusFavorite { name: String, output: String },
+    /// Move the workspace with this id one position in the direction.
+    Move { id: i64, direction: MoveDirection },
+}
+
+/// Sets the contents of a workspace button to its label,
+/// replacing any previous label.
+fn set_button_label(button: &Button, label: WorkspaceLabel, icon_theme: &IconTheme, size: i32) {
+    let input = match label {
+        WorkspaceLabel::Image(input) => input,
+        WorkspaceLabel::Text(text) => {
+            button.set_image(None::<&Image>);
+            button.set_label(text);
+            return;
+        }
+    };
+
+    let image = Image::new();
+    image.add_class("image");
+    image.add_class("icon");
+
+    match ImageProvider::parse(input, icon_theme, false, size)
+        .map(|provider| provider.load_into_image(image.clone()))
+    {
+        Some(_) => {
+            button.set_label("");
+            button.set_image(Some(&image));
+            button.set_always_show_image(true);
+        }
+        None => {
+            button.set_image(None::<&Image>);
+            button.set_label(input);
+        }
+    }
 }
 
 /// Creates a button from a workspace
 fn create_button(
-    name: &str,
-    visibility: Visibility,
-    name_map: &HashMap<String, String>,
+    workspace: &Workspace,
+    name_map: &WorkspaceNameMap,
     icon_theme: &IconTheme,
     icon_size: i32,
-    tx: &Sender<String>,
+    drag_reorder: bool,
+    tx: &Sender<WorkspaceAction>,
 ) -> Button {
-    let label = name_map.get(name).map_or(name, String::as_str);
-
-    let button = new_icon_button(label, icon_theme, icon_size);
+    let name = workspace.name.as_str();
+    let visibility = workspace.visibility;
+
+    let button = Button::new();
+    set_button_label(
+        &button,
+        name_map.label(name, workspace.num),
+        icon_theme,
+        icon_size,
+    );
     button.set_widget_name(name);
+    button.set_tag(SORT_TAG, workspace.sort_key.clone());
+    button.set_tag(ID_TAG, workspace.id);
 
     let style_context = button.style_context();
     style_context.add_class("item");
@@ -144,38 +222,118 @@ fn create_button(
         style_context.add_class("focused");
     }
 
-    if !visibility.is_visible() {
+    if visibility.is_hidden() {
         style_context.add_class("inactive");
     }
 
+    if workspace.urgent {
+        style_context.add_class("urgent");
+    }
+
+    if workspace.is_special {
+        style_context.add_class("special");
+    }
+
     {
         let tx = tx.clone();
-        let name = name.to_string();
-        button.connect_clicked(move |_item| {
-            try_send!(tx, name.clone());
+        button.connect_clicked(move |item| {
+            let action = match item.get_tag::<String>(FAVORITE_TAG) {
+                Some(output) => WorkspaceAction::FocusFavorite {
+                    name: item.widget_name().to_string(),
+                    output: output.clone(),
+                },
+                None => match item.get_tag::<i64>(ID_TAG) {
+                    Some(id) => WorkspaceAction::Focus(*id),
+                    None => return,
+                },
+            };
+
+            try_send!(tx, action);
         });
     }
 
+    if drag_reorder {
+        setup_drag_reorder(&button, tx.clone());
+    }
+
     button
 }
 
+/// Allows the button to be dragged onto another workspace button,
+/// moving its workspace one position towards the other.
+fn setup_drag_reorder(button: &Button, tx: Sender<WorkspaceAction>) {
+    let targets = [TargetEntry::new(DRAG_TARGET, TargetFlags::SAME_APP, 0)];
+
+    button.drag_source_set(ModifierType::BUTTON1_MASK, &targets, DragAction::MOVE);
+    button.drag_dest_set(DestDefaults::ALL, &targets, DragAction::MOVE);
+
+    button.connect_drag_data_get(|button, _, data, _, _| {
+        if let Some(id) = button.get_tag::<i64>(ID_TAG) {
+            data.set_text(&id.to_string());
+        }
+    });
+
+    button.connect_drag_data_received(move |button, _, _, _, data, _, _| {
+        let Some(id) = data.text().and_then(|id| id.parse::<i64>().ok()) else {
+            return;
+        };
+
+        let Some(container) = button.parent() else {
+            return;
+        };
+
+        let Some(container) = container.downcast_ref::<gtk::Box>() else {
+            return;
+        };
+
+        let children = container.children();
+        let source = children
+            .iter()
+            .position(|child| child.get_tag::<i64>(ID_TAG) == Some(&id));
+        let target = children
+            .iter()
+            .position(|child| child == button.upcast_ref::<gtk::Widget>());
+
+        let direction = match source
+            .zip(target)
+            .map(|(source, target)| source.cmp(&target))
+        {
+            Some(Ordering::Less) => MoveDirection::Right,
+            Some(Ordering::Greater) => MoveDirection::Left,
+            Some(Ordering::Equal) | None => return,
+        };
+
+        try_send!(tx, WorkspaceAction::Move { id, direction });
+    });
+}
+
+/// Tag used to store the workspace's `SortKey` on its button.
+const SORT_TAG: &str = "workspace-sort-key";
+
+/// Tag used to store the workspace id on its button.
+const ID_TAG: &str = "workspace-id";
+
+/// Tag used to store the output on buttons for favourites without a workspace.
+const FAVORITE_TAG: &str = "workspace-favorite-output";
+
+/// Drag and drop target for reordering workspace buttons.
+const DRAG_TARGET: &str = "ironbar-workspace";
+
 fn reorder_workspaces(container: &gtk::Box) {
     let mut buttons = container
         .children()
         .into_iter()
-        .map(|child| (child.widget_name().to_string(), child))
+        .map(|child| {
+            let sort_key = child.get_tag::<SortKey>(SORT_TAG).cloned();
+            (sort_key, child.widget_name().to_string(), child)
+        })
         .collect::<Vec<_>>();
 
-    buttons.sort_by(|(label_a, _), (label_b, _a)| {
-        match (label_a.parse::<i32>(), label_b.parse::<i32>()) {
-            (Ok(a), Ok(b)) => a.cmp(&b),
-            (Ok(_), Err(_)) => Ordering::Less,
-            (Err(_), Ok(_)) => Ordering::Greater,
-            (Err(_), Err(_)) => label_a.cmp(label_b),
-        }
+    buttons.sort_by(|(key_a, label_a, _), (key_b, label_b, _)| {
+        key_a.cmp(key_b).then_with(|| label_a.cmp(label_b))
     });
 
-    for (i, (_, button)) in buttons.into_iter().enumerate() {
+    for (i, (_, _, button)) in buttons.into_iter().enumerate() {
         container.reorder_child(&button, i as i32);
     }
 }
@@ -189,16 +347,214 @@ fn find_btn(map: &HashMap<i64, Button>, workspace: &Workspace) -> Option<Button>
         .cloned()
 }
 
+/// Gets the position of a button in the container.
+fn child_position(container: &gtk::Box, button: &Button) -> Option<i32> {
+    container
+        .children()
+        .iter()
+        .position(|child| child == button.upcast_ref::<gtk::Widget>())
+        .map(|position| position as i32)
+}
+
+/// Creates a placeholder for a favourite which has no workspace.
+fn favorite_workspace(name: &str, output: &str) -> Workspace {
+    let num = parse_workspace_num(name);
+
+    Workspace {
+        id: -(Ironbar::unique_id() as i64),
+        name: name.to_string(),
+        num,
+        monitor: output.to_string(),
+        visibility: Visibility::Hidden,
+        urgent: false,
+        is_special: false,
+        sort_key: SortKey::new(num, name),
+    }
+}
+
+/// A change to make to the workspace buttons.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ButtonChange {
+    /// Add a button for a workspace which is not yet shown.
+    Add(Workspace),
+    /// Replace the button for a shown workspace, keeping its position.
+    Refresh(Workspace),
+    /// Remove the button for the workspace with this id.
+    Remove(i64),
+    /// Add an inactive button for a favourite which has no workspace.
+    AddFavorite(String),
+    /// Remove the inactive button for a favourite, as its workspace is now shown.
+    RemoveFavorite(String),
+}
+
+/// The workspaces shown by the module.
+///
+/// Workspace updates are turned into the changes to make to the buttons,
+/// so that buttons for unchanged workspaces are left alone.
+#[derive(Debug, Default)]
+struct ShownWorkspaces {
+    /// Shown workspaces, keyed by id.
+    workspaces: HashMap<i64, Workspace>,
+    /// Names of the favourite workspaces.
+    favorites: Vec<String>,
+    /// Favourites currently shown as inactive buttons.
+    placeholders: HashSet<String>,
+}
+
+impl ShownWorkspaces {
+    fn new(favorites: Vec<String>) -> Self {
+        Self {
+            favorites,
+            ..Self::default()
+        }
+    }
+
+    /// Replaces the shown workspaces,
+    /// only changing those which were added, removed or updated.
+    fn init(
+        &mut self,
+        workspaces: Vec<Workspace>,
+        is_shown: impl Fn(&Workspace) -> bool,
+    ) -> Vec<ButtonChange> {
+        let ids = workspaces.iter().map(|w| w.id).collect::<HashSet<_>>();
+
+        let mut removed = self
+            .workspaces
+            .keys()
+            .filter(|id| !ids.contains(id))
+            .copied()
+            .collect::<Vec<_>>();
+        removed.sort_unstable();
+
+        let mut changes = removed
+            .into_iter()
+            .filter_map(|id| self.remove(id))
+            .collect::<Vec<_>>();
+
+        changes.extend(
+            workspaces
+                .into_iter()
+                .filter_map(|workspace| self.show(workspace, &is_shown)),
+        );
+        changes.extend(self.sync_favorites());
+
+        changes
+    }
+
+    /// Updates the workspaces,
+    /// adding or removing any which should now be shown or hidden.
+    fn update(
+        &mut self,
+        workspaces: Vec<Workspace>,
+        is_shown: impl Fn(&Workspace) -> bool,
+    ) -> Vec<ButtonChange> {
+        let mut changes = workspaces
+            .into_iter()
+            .filter_map(|workspace| self.show(workspace, &is_shown))
+            .collect::<Vec<_>>();
+
+        changes.extend(self.sync_favorites());
+        changes
+    }
+
+    fn remove_workspace(&mut self, id: i64) -> Vec<ButtonChange> {
+        let mut changes = self.remove(id).into_iter().collect::<Vec<_>>();
+        changes.extend(self.sync_favorites());
+        changes
+    }
+
+    /// Records a change of focus.
+    /// The buttons' classes are updated directly, so no changes are returned.
+    fn focus(&mut self, old: Option<&Workspace>, new: &Workspace) {
+        if let Some(old) = old {
+            let same_monitor = old.monitor == new.monitor;
+
+            if let Some(workspace) = self.workspaces.get_mut(&old.id) {
+                workspace.visibility = if same_monitor {
+                    Visibility::Hidden
+                } else {
+                    Visibility::visible()
+                };
+            }
+        }
+
+        if let Some(workspace) = self.workspaces.get_mut(&new.id) {
+            workspace.visibility = Visibility::focused();
+        }
+    }
+
+    /// Records a rename.
+    /// The button is updated directly, so only changes to favourites are returned.
+    fn rename(&mut self, id: i64, name: &str) -> Vec<ButtonChange> {
+        if let Some(workspace) = self.workspaces.get_mut(&id) {
+            let num = parse_workspace_num(name);
+
+            workspace.sort_key = workspace.sort_key.renamed(num, name);
+            workspace.num = num;
+            workspace.name = name.to_string();
+        }
+
+        self.sync_favorites()
+    }
+
+    fn set_urgent(&mut self, id: i64, urgent: bool) {
+        if let Some(workspace) = self.workspaces.get_mut(&id) {
+            workspace.urgent = urgent;
+        }
+    }
+
+    fn show(
+        &mut self,
+        workspace: Workspace,
+        is_shown: impl Fn(&Workspace) -> bool,
+    ) -> Option<ButtonChange> {
+        if !is_shown(&workspace) {
+            return self.remove(workspace.id);
+        }
+
+        match self.workspaces.insert(workspace.id, workspace.clone()) {
+            None => Some(ButtonChange::Add(workspace)),
+            Some(old) if old != workspace => Some(ButtonChange::Refresh(workspace)),
+            Some(_) => None,
+        }
+    }
+
+    fn remove(&mut self, id: i64) -> Option<ButtonChange> {
+        self.workspaces
+            .remove(&id)
+            .map(|_| ButtonChange::Remove(id))
+    }
+
+    /// Shows favourites without a workspace as inactive buttons,
+    /// and removes those for favourites which now have one.
+    fn sync_favorites(&mut self) -> Vec<ButtonChange> {
+        let mut changes = vec![];
+
+        for name in &self.favorites {
+            let has_workspace = self.workspaces.values().any(|w| &w.name == name);
+
+            if has_workspace && self.placeholders.remove(name) {
+                changes.push(ButtonChange::RemoveFavorite(name.clone()));
+            } else if !has_workspace && self.placeholders.insert(name.clone()) {
+                changes.push(ButtonChange::AddFavorite(name.clone()));
+            }
+        }
+
+        changes
+    }
+}
+
 impl WorkspacesModule {
     fn show_workspace_check(&self, output: &String, work: &Workspace) -> bool {
         (work.visibility.is_focused() || !self.hidden.contains(&work.name))
+            && (self.show_special || !work.is_special)
             && (self.all_monitors || output == &work.monitor)
     }
 }
 
 impl Module<gtk::Box> for WorkspacesModule {
     type SendMessage = WorkspaceUpdate;
-    type ReceiveMessage = String;
+    type ReceiveMessage = WorkspaceAction;
 
     module_impl!("workspaces");
 
@@ -209,7 +565,16 @@ impl Module<gtk::Box> for WorkspacesModule {
         mut rx: Receiver<Self::ReceiveMessage>,
     ) -> Result<()> {
         let tx = context.tx.clone();
-        let client = context.ironbar.clients.borrow_mut().workspaces()?;
+        let client = match context.ironbar.clients.borrow_mut().workspaces() {
+            Ok(client) => client,
+            // show an empty module rather than stopping the bar from loading
+            Err(err) if err.is::<UnsupportedCompositorError>() => {
+                warn!("{err:?}");
+                return Ok(());
+            }
+            Err(err) => return Err(err),
+        };
+
         // Subscribe & send events
         spawn(async move {
             let mut srx = client.subscribe_workspace_change();
@@ -224,15 +589,27 @@ impl Module<gtk::Box> for WorkspacesModule {
 
         let client = context.try_client::<dyn WorkspaceClient>()?;
 
-        // Change workspace focus
+        // Focus or move workspaces
         spawn(async move {
             trace!("Setting up UI event handler");
 
-            while let Some(name) = rx.recv().await {
-                client.focus(name)?;
-            }
+            while let Some(action) = rx.recv().await {
+                // a workspace may be gone by the time it is clicked,
+                // so failed requests should not stop the handler
+                let res = match action {
+                    WorkspaceAction::Focus(id) => client.focus(id),
+                    WorkspaceAction::FocusFavorite { name, output } => {
+                        client.focus_by_name_on_output(name, &output)
+                    }
+                    WorkspaceAction::Move { id, direction } => {
+                        client.move_workspace_position(id, direction)
+                    }
+                };
 
-            Ok::<(), Report>(())
+                if let Err(err) = res {
+                    warn!("{err:?}");
+                }
+            }
         });
 
         Ok(())
@@ -245,85 +622,58 @@ impl Module<gtk::Box> for WorkspacesModule {
     ) -> Result<ModuleParts<gtk::Box>> {
         let container = gtk::Box::new(info.bar_position.orientation(), 0);
 
-        let name_map = self.name_map.clone().unwrap_or_default();
-        let favs = self.favorites.clone();
-        let mut fav_names: Vec<String> = vec![];
+        let name_map = self.name_map.clone();
 
         let mut button_map: HashMap<i64, Button> = HashMap::new();
+        let mut favorite_map: HashMap<String, Button> = HashMap::new();
 
         {
             let container = container.clone();
             let output_name = info.output_name.to_string();
             let icon_theme = info.icon_theme.clone();
-            let icon_size = self.icon_size;
-
-            // keep track of whether init event has fired previously
-            // since it fires for every workspace subscriber
-            let mut has_initialized = false;
+            let icon_size = self.icon_size.resolve(&container);
 
-            glib_recv!(context.subscribe(), event => {
-                match event {
-                    WorkspaceUpdate::Init(workspaces) => {
-                        if !has_initialized {
-                            trace!("Creating workspace buttons");
-
-                            let mut added = HashSet::new();
-
-                            let mut add_workspace = |id: i64, name: &str, visibility: Visibility| {
-                                let item = create_button(
-                                    name,
-                                    visibility,
-                                    &name_map,
-                                    &icon_theme,
-                                    icon_size,
-                                    &context.controller_tx,
-                                );
-
-                                container.add(&item);
-                                button_map.insert(id, item);
-                            };
-
-                            // add workspaces from client
-                            for workspace in &workspaces {
-                                if self.show_workspace_check(&output_name, workspace) {
-                                    add_workspace(workspace.id, &workspace.name, workspace.visibility);
-                                    added.insert(workspace.name.to_string());
-                                }
-                            }
+            let favorites = match &self.favorites {
+                Favorites::Global(names) => names.clone(),
+                Favorites::ByMonitor(map) => map.get(&output_name).cloned().unwrap_or_default(),
+            };
 
-                            let mut add_favourites = |names: &Vec<String>| {
-                                for name in names {
-                                    fav_names.push(name.to_string());
-
-                                    if !added.contains(name) {
-                                        // Favourites are added with the same name and ID
-                                        // as Hyprland will initialize them this way.
-                                        // Since existing workspaces are added above,
-                                        // this means there shouldn't be any issues with renaming.
-                                        add_workspace(-(Ironbar::unique_id() as i64), name, Visibility::Hidden);
-                                        added.insert(name.to_string());
-                                    }
-                                }
-                            };
-
-                            // add workspaces from favourites
-                            match &favs {
-                                Favorites::Global(names) => add_favourites(names),
-                                Favorites::ByMonitor(map) => {
-                                    if let Some(to_add) = map.get(&output_name) {
-                                        add_favourites(to_add);
-                                    }
-                                }
-                            }
+            let mut shown = ShownWorkspaces::new(favorites);
 
-                            if self.sort == SortOrder::Alphanumeric {
-                                reorder_workspaces(&container);
-                            }
+            glib_recv!(context.subscribe(), event => {
+                let is_shown = |workspace: &Workspace| self.show_workspace_check(&output_name, workspace);
+
+                let create = |workspace: &Workspace| {
+                    create_button(
+                        workspace,
+                        &name_map,
+                        &icon_theme,
+                        icon_size,
+                        self.drag_reorder,
+                        &context.controller_tx,
+                    )
+                };
 
-                            container.show_all();
-                            has_initialized = true;
-                        }
+                let changes = match event {
+                    WorkspaceUpdate::Init(workspaces) => {
+                        trace!("Updating workspace buttons");
+
+                        let workspaces = if self.all_monitors {
+                            workspaces
+                        } else {
+                            filter_by_output(&workspaces, &output_name)
+                                .into_iter()
+                                .cloned()
+                                .collect()
+                        };
+
+                        shown.init(workspaces, is_shown)
+                    }
+                    WorkspaceUpdate::Update(workspaces) => shown.update(workspaces, is_shown),
+                    WorkspaceUpdate::Add(workspace) | WorkspaceUpdate::Move(workspace) => {
+                        shown.update(vec![workspace], is_shown)
                     }
+                    WorkspaceUpdate::Remove(id) => shown.remove_workspace(id),
                     WorkspaceUpdate::Focus { old, new } => {
                         if let Some(btn) = old.as_ref().and_then(|w| find_btn(&button_map, w)) {
                             if Some(new.monitor.as_str()) == old.as_ref().map(|w| w.monitor.as_str()) {
@@ -337,84 +687,119 @@ impl Module<gtk::Box> for WorkspacesModule {
                             btn.add_class("visible");
                             btn.add_class("focused");
                         }
+
+                        shown.focus(old.as_ref(), &new);
+                        vec![]
                     }
                     WorkspaceUpdate::Rename { id, name } => {
                         if let Some(btn) = button_map.get(&id) {
-                            let name = name_map.get(&name).unwrap_or(&name);
-                            btn.set_label(name);
-                        }
-                    }
-                    WorkspaceUpdate::Add(workspace) => {
-                        if fav_names.contains(&workspace.name) {
-                            let btn = button_map.get(&workspace.id);
-                            if let Some(btn) = btn {
-                                btn.style_context().remove_class("inactive");
-                            }
-                        } else if self.show_workspace_check(&output_name, &workspace) {
-                            let name = workspace.name;
-                            let item = create_button(
-                                &name,
-                                workspace.visibility,
-                                &name_map,
+                            let num = parse_workspace_num(&name);
+                            let sort_key = btn
+                                .get_tag::<SortKey>(SORT_TAG)
+                                .map_or_else(|| SortKey::new(num, &name), |key| key.renamed(num, &name));
+
+                            btn.set_widget_name(&name);
+                            btn.set_tag(SORT_TAG, sort_key);
+
+                            set_button_label(
+                                btn,
+                                name_map.label(&name, num),
                                 &icon_theme,
                                 icon_size,
-                                &context.controller_tx,
                             );
 
-                            container.add(&item);
                             if self.sort == SortOrder::Alphanumeric {
                                 reorder_workspaces(&container);
                             }
+                        }
 
-                            item.show();
-
-                            if !name.is_empty() {
-                                button_map.insert(workspace.id, item);
+                        shown.rename(id, &name)
+                    }
+                    WorkspaceUpdate::Urgent { id, urgent } => {
+                        if let Some(btn) = button_map.get(&id) {
+                            if urgent {
+                                btn.add_class("urgent");
+                            } else {
+                                btn.style_context().remove_class("urgent");
                             }
                         }
+
+                        shown.set_urgent(id, urgent);
+                        vec![]
                     }
-                    WorkspaceUpdate::Move(workspace) => {
-                        if !self.hidden.contains(&workspace.name) && !self.all_monitors {
-                            if workspace.monitor == output_name {
-                                let name = workspace.name;
-                                let item = create_button(
-                                    &name,
-                                    workspace.visibility,
-                                    &name_map,
-                                    &icon_theme,
-                                    icon_size,
-                                    &context.controller_tx,
-                                );
-
-                                container.add(&item);
-
-                                if self.sort == SortOrder::Alphanumeric {
-                                    reorder_workspaces(&container);
-                                }
+                    WorkspaceUpdate::Unknown => {
+                        warn!("Received unknown type workspace event");
+                        vec![]
+                    }
+                };
+
+                let reorder = !changes.is_empty() && self.sort == SortOrder::Alphanumeric;
+
+                // position of the last removed button,
+                // so a favourite's inactive button can take its place
+                let mut removed_position = None;
+
+                for change in changes {
+                    match change {
+                        ButtonChange::Add(workspace) => {
+                            let item = create(&workspace);
+                            container.add(&item);
+
+                            // take the place of the favourite's inactive button
+                            if let Some(position) = favorite_map
+                                .get(&workspace.name)
+                                .and_then(|button| child_position(&container, button))
+                            {
+                                container.reorder_child(&item, position);
+                            }
 
-                                item.show();
+                            item.show();
+                            button_map.insert(workspace.id, item);
+                        }
+                        ButtonChange::Refresh(workspace) => {
+                            let item = create(&workspace);
+                            container.add(&item);
 
-                                if !name.is_empty() {
-                                    button_map.insert(workspace.id, item);
+                            // keep refreshed buttons in place
+                            if let Some(button) = button_map.insert(workspace.id, item.clone()) {
+                                if let Some(position) = child_position(&container, &button) {
+                                    container.reorder_child(&item, position);
                                 }
-                            } else if let Some(item) = button_map.get(&workspace.id) {
-                                container.remove(item);
+
+                                container.remove(&button);
                             }
+
+                            item.show();
                         }
-                    }
-                    WorkspaceUpdate::Remove(workspace) => {
-                        let button = button_map.get(&workspace);
-                        if let Some(item) = button {
-                            if workspace < 0 {
-                            // if fav_names.contains(&workspace) {
-                                item.style_context().add_class("inactive");
-                            } else {
-                                container.remove(item);
+                        ButtonChange::Remove(id) => {
+                            if let Some(button) = button_map.remove(&id) {
+                                removed_position = child_position(&container, &button);
+                                container.remove(&button);
+                            }
+                        }
+                        ButtonChange::AddFavorite(name) => {
+                            let item = create(&favorite_workspace(&name, &output_name));
+                            item.set_tag(FAVORITE_TAG, output_name.clone());
+                            container.add(&item);
+
+                            if let Some(position) = removed_position.take() {
+                                container.reorder_child(&item, position);
+                            }
+
+                            item.show();
+                            favorite_map.insert(name, item);
+                        }
+                        ButtonChange::RemoveFavorite(name) => {
+                            if let Some(button) = favorite_map.remove(&name) {
+                                container.remove(&button);
                             }
                         }
                     }
-                    WorkspaceUpdate::Unknown => warn!("Received unknown type workspace event")
-                };
+                }
+
+                if reorder {
+                    reorder_workspaces(&container);
+                }
             });
         }
 
@@ -424,3 +809,142 @@ impl Module<gtk::Box> for WorkspacesModule {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(id: i64, name: &str) -> Workspace {
+        let num = parse_workspace_num(name);
+
+        Workspace {
+            id,
+            name: name.to_string(),
+            num,
+            monitor: String::from("DP-1"),
+            visibility: Visibility::Hidden,
+            urgent: false,
+            is_special: false,
+            sort_key: SortKey::new(num, name),
+        }
+    }
+
+    fn shown(_: &Workspace) -> bool {
+        true
+    }
+
+    #[test]
+    fn test_init_adds_workspaces() {
+        let mut workspaces = ShownWorkspaces::default();
+
+        let changes = workspaces.init(vec![workspace(1, "1"), workspace(2, "2")], shown);
+
+        assert_eq!(
+            changes,
+            vec![
+                ButtonChange::Add(workspace(1, "1")),
+                ButtonChange::Add(workspace(2, "2"))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_repeated_init_keeps_unchanged_workspaces() {
+        let mut workspaces = ShownWorkspaces::default();
+        workspaces.init(vec![workspace(1, "1"), workspace(2, "2")], shown);
+
+        let changes = workspaces.init(vec![workspace(1, "1"), workspace(2, "2")], shown);
+
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn test_init_only_changes_differences() {
+        let mut workspaces = ShownWorkspaces::default();
+        workspaces.init(vec![workspace(1, "1"), workspace(2, "2")], shown);
+
+        let mut focused = workspace(2, "2");
+        focused.visibility = Visibility::focused();
+
+        let changes = workspaces.init(vec![focused.clone(), workspace(3, "3")], shown);
+
+        assert_eq!(
+            changes,
+            vec![
+                ButtonChange::Remove(1),
+                ButtonChange::Refresh(focused),
+                ButtonChange::Add(workspace(3, "3"))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unnamed_workspace_is_removed() {
+        let mut workspaces = ShownWorkspaces::default();
+        workspaces.update(vec![workspace(1, "")], shown);
+
+        assert_eq!(
+            workspaces.remove_workspace(1),
+            vec![ButtonChange::Remove(1)]
+        );
+    }
+
+    #[test]
+    fn test_hidden_workspace_is_removed() {
+        let mut workspaces = ShownWorkspaces::default();
+        workspaces.init(vec![workspace(1, "1")], shown);
+
+        let mut moved = workspace(1, "1");
+        moved.monitor = String::from("DP-2");
+
+        let changes = workspaces.update(vec![moved], |w| w.monitor == "DP-1");
+
+        assert_eq!(changes, vec![ButtonChange::Remove(1)]);
+    }
+
+    #[test]
+    fn test_favorites_replaced_by_workspaces() {
+        let mut workspaces = ShownWorkspaces::new(vec![String::from("1"), String::from("2")]);
+
+        let changes = workspaces.init(vec![workspace(1, "1")], shown);
+        assert_eq!(
+            changes,
+            vec![
+                ButtonChange::Add(workspace(1, "1")),
+                ButtonChange::AddFavorite(String::from("2"))
+            ]
+        );
+
+        let changes = workspaces.update(vec![workspace(2, "2")], shown);
+        assert_eq!(
+            changes,
+            vec![
+                ButtonChange::Add(workspace(2, "2")),
+                ButtonChange::RemoveFavorite(String::from("2"))
+            ]
+        );
+
+        let changes = workspaces.remove_workspace(1);
+        assert_eq!(
+            changes,
+            vec![
+                ButtonChange::Remove(1),
+                ButtonChange::AddFavorite(String::from("1"))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_focus_is_kept_across_init() {
+        let mut workspaces = ShownWorkspaces::default();
+        workspaces.init(vec![workspace(1, "1"), workspace(2, "2")], shown);
+
+        let mut new = workspace(2, "2");
+        new.visibility = Visibility::focused();
+        workspaces.focus(Some(&workspace(1, "1")), &new);
+
+        let changes = workspaces.init(vec![workspace(1, "1"), new], shown);
+
+        assert!(changes.is_empty());
+    }
+}
@@ -159,6 +159,24 @@ impl Popup {
             .insert(key, PopupCacheValue { name, content });
     }
 
+    /// Removes the popup content registered for the provided widget,
+    /// along with the buttons used to open it.
+    pub fn unregister_content(&self, key: usize) {
+        if self.current_widget() == Some(key) {
+            self.hide();
+        }
+
+        let removed = self.container_cache.borrow_mut().remove(&key);
+
+        if let Some(PopupCacheValue { content, .. }) = removed {
+            debug!("Unregistered popup content for #{}", key);
+
+            self.button_cache
+                .borrow_mut()
+                .retain(|button| !content.buttons.contains(button));
+        }
+    }
+
     pub fn show(&self, widget_id: usize, button_id: usize) {
         self.clear_window();
 
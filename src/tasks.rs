@@ -0,0 +1,75 @@
+use crate::lock;
+use std::cell::RefCell;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::task::AbortHandle;
+
+tokio::task_local! {
+    static TASK_GROUP: Option<TaskGroup>;
+}
+
+thread_local! {
+    static ENTERED: RefCell<Option<TaskGroup>> = const { RefCell::new(None) };
+}
+
+/// A set of tasks which are stopped together.
+///
+/// Tasks spawned using [`crate::spawn`] while a group is entered belong to it,
+/// as do any tasks which those tasks go on to spawn.
+/// This allows everything a module instance starts to be torn down
+/// without each module needing to track its own tasks.
+#[derive(Debug, Clone, Default)]
+pub struct TaskGroup(Arc<Mutex<Vec<AbortHandle>>>);
+
+impl TaskGroup {
+    /// Runs `f` with the group entered on the current thread.
+    pub fn enter<R>(&self, f: impl FnOnce() -> R) -> R {
+        let prev = ENTERED.with(|entered| entered.replace(Some(self.clone())));
+        let res = f();
+        ENTERED.with(|entered| entered.replace(prev));
+        res
+    }
+
+    /// Aborts every task in the group.
+    /// Tasks spawned into the group afterwards are unaffected.
+    pub fn abort(&self) {
+        for handle in lock!(self.0).drain(..) {
+            handle.abort();
+        }
+    }
+
+    /// Wraps a future so that any tasks it spawns also join this group.
+    pub(crate) fn scope<F: Future>(&self, f: F) -> impl Future<Output = F::Output> {
+        TASK_GROUP.scope(Some(self.clone()), f)
+    }
+
+    pub(crate) fn add(&self, handle: AbortHandle) {
+        let mut handles = lock!(self.0);
+        handles.retain(|handle| !handle.is_finished());
+        handles.push(handle);
+    }
+
+    /// Gets the group which newly spawned tasks join, if any.
+    pub(crate) fn current() -> Option<Self> {
+        TASK_GROUP
+            .try_with(Clone::clone)
+            .ok()
+            .flatten()
+            .or_else(|| ENTERED.with(|entered| entered.borrow().clone()))
+    }
+}
+
+/// Runs `f` outside of any task group,
+/// so that tasks it spawns are not stopped along with the current group.
+///
+/// This is for shared state, such as clients,
+/// which may be created lazily by whichever module first uses it.
+pub fn detached<R>(f: impl FnOnce() -> R) -> R {
+    let prev = ENTERED.with(|entered| entered.replace(None));
+    let res = match TASK_GROUP.try_with(|_| ()) {
+        Ok(()) => TASK_GROUP.sync_scope(None, f),
+        Err(_) => f(),
+    };
+    ENTERED.with(|entered| entered.replace(prev));
+    res
+}